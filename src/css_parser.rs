@@ -1,72 +1,166 @@
+use std::ops::Range;
+use crate::css_parser::at_rule::AtRule;
 use crate::css_parser::color_value::ColorValue;
+use crate::css_parser::combinator::Combinator;
 use crate::css_parser::declaration::Declaration;
 use crate::css_parser::declaration_value::DeclarationValue;
+use crate::css_parser::dropped_construct::DroppedConstruct;
 use crate::css_parser::length_unit::LengthUnit;
+use crate::css_parser::parse_warning::ParseWarning;
 use crate::css_parser::rule::Rule;
 use crate::css_parser::selector::Selector;
 use crate::css_parser::stylesheet::Stylesheet;
-use crate::general_parser::GeneralParser;
+use crate::css_parser::token::Token;
+use crate::css_parser::tokenizer::Tokenizer;
+use crate::encoding::character_encoding::CharacterEncoding;
+use crate::encoding::decode_css;
+use crate::parse_error::{ParseError, ParseErrorKind};
+use crate::source_span::SourceSpan;
 
 pub mod stylesheet;
 pub mod rule;
+pub mod at_rule;
 pub mod selector;
+pub mod combinator;
 pub mod declaration;
 pub mod declaration_value;
 pub mod length_unit;
 pub mod color_value;
+pub mod parse_warning;
+pub mod dropped_construct;
+pub mod token;
+pub mod tokenizer;
 
 pub struct CSSParser {
-    general_parser: GeneralParser
+    tokenizer: Tokenizer,
+    // The token read from the tokenizer but not consumed yet, if any.
+    next_token: Option<(Token, Range<usize>)>,
+    // Where the last consumed token ends, which is where the construct being parsed ends so far.
+    previous_token_end: usize,
+    file_id: usize,
+    // Where the CSS starts in its file, which is not 1:1 for the content of a <style> element.
+    start_line: usize,
+    start_column: usize,
+    warnings: Vec<ParseWarning>
 }
 
 impl CSSParser {
-    pub fn parse(&mut self) -> Stylesheet {
-        Stylesheet {
-            rules: self.parser_rules()
+    pub fn new(content: String) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(content),
+            next_token: None,
+            previous_token_end: 0,
+            file_id: 0,
+            start_line: 1,
+            start_column: 1,
+            warnings: Vec::new()
         }
     }
 
-    pub fn parser_rules(&mut self) -> Vec<Rule> {
-        let mut rules = Vec::new();
+    // The environment encoding is the one of the referring document, used when the stylesheet
+    // has neither a byte order mark nor an @charset rule.
+    pub fn from_bytes(bytes: &[u8], environment_encoding: Option<CharacterEncoding>) -> Self {
+        return Self::new(decode_css(bytes, environment_encoding));
+    }
+
+    // Sets the file and the position in it that the source spans of rules, selectors and declarations refer to.
+    pub fn set_source_location(&mut self, file_id: usize, line: usize, column: usize) {
+        self.file_id = file_id;
+        self.start_line = line;
+        self.start_column = column;
+    }
+
+    // The constructs dropped while recovering from errors so far.
+    pub fn get_warnings(&self) -> &[ParseWarning] {
+        return &self.warnings;
+    }
+
+    // Never fails: invalid rules and declarations are dropped and reported in the warnings.
+    pub fn parse(&mut self) -> Stylesheet {
+        let mut stylesheet = Stylesheet {
+            rules: Vec::new(),
+            at_rules: Vec::new()
+        };
+
+        loop {
+            self.consume_whitespaces();
 
-        while !self.general_parser.is_eof() {
-            self.general_parser.consume_whitespaces();
-            let rule = self.parse_rule();
-            rules.push(rule);
+            match self.peek_token() {
+                Token::Eof => break,
+                Token::Cdo | Token::Cdc => {
+                    self.pop_token();
+                },
+                Token::AtKeyword(_) => match self.parse_at_rule() {
+                    Ok(at_rule) => stylesheet.at_rules.push(at_rule),
+                    Err(error) => self.drop_invalid_rule(DroppedConstruct::AtRule, error)
+                },
+                _ => match self.parse_rule() {
+                    Ok(rule) => stylesheet.rules.push(rule),
+                    Err(error) => self.drop_invalid_rule(DroppedConstruct::Rule, error)
+                }
+            }
         }
 
-        return rules;
+        return stylesheet;
     }
 
-    pub fn parse_rule(&mut self) -> Rule {
-        let selectors = self.parse_selectors();
-        let declarations = self.parse_declarations();
+    // Parses any at-rule without interpreting it: the name, the prelude up to the first '{', ';' or '}',
+    // and the block if there is one. A block left open at the end of the input is closed there.
+    pub fn parse_at_rule(&mut self) -> Result<AtRule, ParseError> {
+        let start_position = self.peek_range().start;
+        let name = match self.peek_token() {
+            Token::AtKeyword(name) => name.to_ascii_lowercase(),
+            _ => return Err(self.create_unexpected_token_error())
+        };
+        self.pop_token();
+
+        let prelude = self.consume_component_values_until(|token| matches!(token, Token::OpenCurly | Token::Semicolon | Token::CloseCurly));
+        let block = match self.peek_token() {
+            Token::OpenCurly => {
+                self.pop_token();
+                Some(self.consume_block_contents())
+            },
+            Token::Semicolon => {
+                self.pop_token();
+                None
+            },
+            _ => None
+        };
 
-        Rule {
+        return Ok(AtRule {
+            name,
+            prelude: String::from(prelude.trim()),
+            block,
+            source_span: self.create_source_span(start_position)
+        });
+    }
+
+    pub fn parse_rule(&mut self) -> Result<Rule, ParseError> {
+        let start_position = self.peek_range().start;
+        let selectors = self.parse_selectors()?;
+        let declarations = self.parse_declarations()?;
+
+        Ok(Rule {
             selectors,
-            declarations
-        }
+            declarations,
+            source_span: self.create_source_span(start_position)
+        })
     }
 
-    pub fn parse_selectors(&mut self) -> Vec<Selector> {
+    pub fn parse_selectors(&mut self) -> Result<Vec<Selector>, ParseError> {
         let mut selectors = Vec::new();
 
         loop {
-            selectors.push(self.parse_selector());
-            self.general_parser.consume_whitespaces();
-
-            match self.general_parser.peek_current_char() {
-                ',' => {
-                    self.general_parser.pop_current_char();
-                    self.general_parser.consume_whitespaces();
-                },
-                '{' => {
-                    break;
+            selectors.push(self.parse_selector()?);
+            self.consume_whitespaces();
+
+            match self.peek_token() {
+                Token::Eof | Token::OpenCurly => break,
+                Token::Comma => {
+                    self.pop_token();
+                    self.consume_whitespaces();
                 },
-                c => {
-                    println!("Unexpected character in CSS selector: {}. ", c);
-                    panic!("Unexpected character in CSS selector: {}. ", c)
-                }
+                _ => return Err(self.create_unexpected_token_error())
             }
         }
 
@@ -74,145 +168,570 @@ impl CSSParser {
             a.get_specificity().cmp(&b.get_specificity())
         });
 
-        return selectors;
+        return Ok(selectors);
+    }
+
+    // Parses a selector list that makes up the whole input, such as the argument of query_selector.
+    pub fn parse_selector_list(&mut self) -> Result<Vec<Selector>, ParseError> {
+        self.consume_whitespaces();
+        let selectors = self.parse_selectors()?;
+
+        if *self.peek_token() != Token::Eof {
+            return Err(self.create_unexpected_token_error());
+        }
+
+        return Ok(selectors);
+    }
+
+    // Parses compound selectors joined by combinators. The result is the last compound,
+    // which refers to the ones before it.
+    pub fn parse_selector(&mut self) -> Result<Selector, ParseError> {
+        let start_position = self.peek_range().start;
+        let mut selector = self.parse_compound_selector()?;
+
+        loop {
+            let has_whitespace = *self.peek_token() == Token::Whitespace;
+            self.consume_whitespaces();
+
+            let combinator = match self.peek_token() {
+                Token::Delim(char @ ('>' | '+' | '~')) => {
+                    let combinator = match char {
+                        '>' => Combinator::Child,
+                        '+' => Combinator::NextSibling,
+                        _ => Combinator::SubsequentSibling
+                    };
+
+                    self.pop_token();
+                    self.consume_whitespaces();
+                    combinator
+                },
+                token if has_whitespace && is_compound_selector_start(token) => Combinator::Descendant,
+                _ => break
+            };
+
+            // Fails for a combinator with nothing after it, as in "p > {". One with nothing before it,
+            // as in "> p", already failed as an empty compound.
+            let mut compound_selector = self.parse_compound_selector()?;
+            compound_selector.combinator = Some((combinator, Box::new(selector)));
+            compound_selector.source_span = self.create_source_span(start_position);
+            selector = compound_selector;
+        }
+
+        return Ok(selector);
     }
 
-    pub fn parse_selector(&mut self) -> Selector {
+    pub fn parse_compound_selector(&mut self) -> Result<Selector, ParseError> {
+        // An empty compound, as in "{ color: red }" or "p, {", is an error rather than an implied '*',
+        // so that a malformed rule is dropped instead of matching every element.
+        if !is_compound_selector_start(self.peek_token()) {
+            return Err(self.create_unexpected_token_error());
+        }
+
+        let start_position = self.peek_range().start;
         let mut selector = Selector {
             tag_name: None,
             id: None,
-            classes: Vec::new()
+            classes: Vec::new(),
+            combinator: None,
+            source_span: None
         };
 
-        while !self.general_parser.is_eof() {
-            match self.general_parser.peek_current_char() {
+        while is_compound_selector_start(self.peek_token()) {
+            match self.pop_token().0 {
+                Token::Ident(tag_name) => selector.tag_name = Some(tag_name),
+                Token::Hash { value, .. } => selector.id = Some(value),
                 // Next is class name
-                '.' => {
-                    self.general_parser.pop_current_char();
-                    selector.classes.push(self.general_parser.consume_while(is_valid_identifier_char));
-                },
-                // Next is id
-                '#' => {
-                    self.general_parser.pop_current_char();
-                    selector.id = Some(self.general_parser.consume_while(is_valid_identifier_char));
-                },
-                // Next is universal identifier
-                '*' => {
-                    self.general_parser.pop_current_char();
-                },
-                // Next is tag name
-                c if is_valid_identifier_char(c) => {
-                    selector.tag_name = Some(self.general_parser.consume_while(is_valid_identifier_char));
-                },
-                _ => break
+                Token::Delim('.') => selector.classes.push(self.pop_ident()?),
+                // Universal selector
+                _ => {}
             }
         }
 
-        return selector;
+        selector.source_span = self.create_source_span(start_position);
+        return Ok(selector);
+    }
+
+    // Only fails if the block does not start with '{'. A block left open at the end of the input is closed there.
+    pub fn parse_declarations(&mut self) -> Result<Vec<Declaration>, ParseError> {
+        self.expect_token(Token::OpenCurly)?;
+        let declarations = self.parse_declaration_list(true);
+
+        if *self.peek_token() == Token::CloseCurly {
+            self.pop_token();
+        }
+
+        return Ok(declarations);
     }
 
-    pub fn parse_declarations(&mut self) -> Vec<Declaration> {
+    // Parses a declaration list without braces, as found in a style attribute.
+    pub fn parse_inline_declarations(&mut self) -> Vec<Declaration> {
+        return self.parse_declaration_list(false);
+    }
+
+    // Parses declarations up to the end of the input, or up to the '}' closing the block if in_block is set.
+    // The last declaration may omit its semicolon. Invalid declarations and at-rules are dropped
+    // up to the next ';' outside of nested blocks.
+    fn parse_declaration_list(&mut self, in_block: bool) -> Vec<Declaration> {
         let mut declarations = Vec::new();
-        assert_eq!('{', self.general_parser.pop_current_char());
-        self.general_parser.consume_whitespaces();
 
-        while !self.general_parser.is_eof() {
-            declarations.push(self.parse_declaration());
-            self.general_parser.consume_whitespaces();
+        loop {
+            self.consume_whitespaces();
 
-            if self.general_parser.peek_current_char() == '}' {
-                self.general_parser.pop_current_char();
-                break;
+            match self.peek_token() {
+                Token::Eof => break,
+                Token::CloseCurly if in_block => break,
+                Token::Semicolon => {
+                    self.pop_token();
+                },
+                Token::AtKeyword(_) => {
+                    let start_position = self.peek_range().start;
+                    match self.parse_at_rule() {
+                        Ok(at_rule) => {
+                            let kind = ParseErrorKind::UnexpectedAtRule(at_rule.name);
+                            let error = self.tokenizer.create_error(kind, start_position..self.previous_token_end);
+                            self.warnings.push(ParseWarning { dropped: DroppedConstruct::AtRule, error });
+                        },
+                        Err(error) => self.drop_invalid_declaration(DroppedConstruct::AtRule, error, in_block)
+                    }
+                },
+                _ => match self.parse_declaration_in_list(in_block) {
+                    Ok(declaration) => declarations.push(declaration),
+                    Err(error) => self.drop_invalid_declaration(DroppedConstruct::Declaration, error, in_block)
+                }
             }
         }
 
         return declarations;
     }
 
-    pub fn parse_declaration(&mut self) -> Declaration {
-        let name = self.general_parser.consume_while(is_valid_identifier_char);
-        self.general_parser.consume_whitespaces();
-        assert_eq!(':', self.general_parser.pop_current_char());
-        self.general_parser.consume_whitespaces();
-        let value = self.parse_declaration_value();
-        assert_eq!(';', self.general_parser.pop_current_char());
+    // A declaration in a list must be followed by ';', or by the end of the list.
+    fn parse_declaration_in_list(&mut self, in_block: bool) -> Result<Declaration, ParseError> {
+        let declaration = self.parse_declaration()?;
+
+        match self.peek_token() {
+            Token::Eof => {},
+            Token::CloseCurly if in_block => {},
+            Token::Semicolon => {
+                self.pop_token();
+            },
+            _ => return Err(self.create_unexpected_token_error())
+        }
+
+        return Ok(declaration);
+    }
 
-        return Declaration {
+    pub fn parse_declaration(&mut self) -> Result<Declaration, ParseError> {
+        let start_position = self.peek_range().start;
+        let name = self.pop_ident()?;
+        self.consume_whitespaces();
+        self.expect_token(Token::Colon)?;
+        self.consume_whitespaces();
+        let value = self.parse_declaration_value()?;
+        let source_span = self.create_source_span(start_position);
+        self.consume_whitespaces();
+        self.consume_important()?;
+
+        return Ok(Declaration {
             name,
-            value
+            value,
+            source_span
+        });
+    }
+
+    // Skips a trailing "!important", which is accepted but has no effect on the cascade here.
+    // Any other '!' after a value is an error.
+    fn consume_important(&mut self) -> Result<(), ParseError> {
+        if *self.peek_token() != Token::Delim('!') {
+            return Ok(());
+        }
+
+        let range = self.peek_range();
+        self.pop_token();
+        self.consume_whitespaces();
+
+        if !matches!(self.peek_token(), Token::Ident(keyword) if keyword.eq_ignore_ascii_case("important")) {
+            return Err(self.tokenizer.create_error(ParseErrorKind::UnexpectedCharacter('!'), range));
+        }
+
+        self.pop_token();
+        self.consume_whitespaces();
+        return Ok(());
+    }
+
+    // Values are a single keyword, a hex color or a length in px. A length of 0 may omit its unit.
+    pub fn parse_declaration_value(&mut self) -> Result<DeclarationValue, ParseError> {
+        let range = self.peek_range();
+
+        if !matches!(self.peek_token(), Token::Ident(_) | Token::Hash { .. } | Token::Number(_) | Token::Percentage(_) | Token::Dimension(_, _)) {
+            let kind = if *self.peek_token() == Token::Eof { ParseErrorKind::UnexpectedEof } else { ParseErrorKind::ExpectedIdentifier };
+            return Err(self.tokenizer.create_error(kind, range));
+        }
+
+        let kind = match self.pop_token().0 {
+            Token::Ident(keyword) => return Ok(DeclarationValue::Keyword(keyword)),
+            Token::Hash { value, .. } => match convert_hex_to_rgb(value.as_str()) {
+                Some((r, g, b)) => return Ok(DeclarationValue::Color(ColorValue { r, g, b, a: 255 })),
+                None => ParseErrorKind::InvalidColor(value)
+            },
+            Token::Dimension(number, unit) if unit.eq_ignore_ascii_case("px") => return Ok(DeclarationValue::Length(number, LengthUnit::Px)),
+            Token::Dimension(_, unit) => ParseErrorKind::InvalidLengthUnit(unit.to_ascii_lowercase()),
+            Token::Number(number) if number == 0.0 => return Ok(DeclarationValue::Length(0.0, LengthUnit::Px)),
+            Token::Percentage(_) => ParseErrorKind::InvalidLengthUnit(String::from("%")),
+            _ => ParseErrorKind::InvalidLengthUnit(String::new())
+        };
+
+        return Err(self.tokenizer.create_error(kind, range));
+    }
+}
+
+impl CSSParser {
+    fn peek_token(&mut self) -> &Token {
+        return &self.next_token.get_or_insert_with(|| self.tokenizer.next_token()).0;
+    }
+
+    fn peek_range(&mut self) -> Range<usize> {
+        return self.next_token.get_or_insert_with(|| self.tokenizer.next_token()).1.clone();
+    }
+
+    fn pop_token(&mut self) -> (Token, Range<usize>) {
+        let token = match self.next_token.take() {
+            Some(token) => token,
+            None => self.tokenizer.next_token()
+        };
+        self.previous_token_end = token.1.end;
+
+        return token;
+    }
+
+    fn pop_ident(&mut self) -> Result<String, ParseError> {
+        return match self.pop_token() {
+            (Token::Ident(name), _) => Ok(name),
+            (Token::Eof, range) => Err(self.tokenizer.create_error(ParseErrorKind::UnexpectedEof, range)),
+            (token, range) => {
+                // Put the token back, so that recovery starts from it.
+                self.next_token = Some((token, range.clone()));
+                Err(self.tokenizer.create_error(ParseErrorKind::ExpectedIdentifier, range))
+            }
         };
     }
 
-    pub fn parse_declaration_value(&mut self) -> DeclarationValue {
-        return match self.general_parser.peek_current_char() {
-            '#' => self.parse_color(),
-            '0'..='9' => self.parse_length(),
-            _ => self.parse_keyword()
+    fn expect_token(&mut self, expected: Token) -> Result<(), ParseError> {
+        if *self.peek_token() == expected {
+            self.pop_token();
+            return Ok(());
+        }
+
+        let range = self.peek_range();
+        return match (expected.get_char(), self.peek_token().get_char()) {
+            (Some(expected), Some(found)) => Err(self.tokenizer.create_error(ParseErrorKind::ExpectedCharacter { expected, found }, range)),
+            _ => Err(self.create_unexpected_token_error())
+        };
+    }
+
+    // Creates an error for the next token, which is left unconsumed.
+    fn create_unexpected_token_error(&mut self) -> ParseError {
+        let range = self.peek_range();
+        let kind = match self.peek_token() {
+            Token::Eof => ParseErrorKind::UnexpectedEof,
+            token => match token.get_char() {
+                Some(char) => ParseErrorKind::UnexpectedCharacter(char),
+                None => ParseErrorKind::UnexpectedToken(format!("{}", token))
+            }
+        };
+
+        return self.tokenizer.create_error(kind, range);
+    }
+
+    fn consume_whitespaces(&mut self) {
+        while *self.peek_token() == Token::Whitespace {
+            self.pop_token();
+        }
+    }
+
+    // Reports the error, then skips the rest of the rule: its prelude and its block.
+    fn drop_invalid_rule(&mut self, dropped: DroppedConstruct, error: ParseError) {
+        self.warnings.push(ParseWarning { dropped, error });
+        self.consume_component_values_until(|token| *token == Token::OpenCurly);
+
+        if *self.peek_token() == Token::OpenCurly {
+            self.pop_token();
+            self.consume_block_contents();
         }
     }
 
-    pub fn parse_color(&mut self) -> DeclarationValue {
-        assert_eq!('#', self.general_parser.pop_current_char());
-        let hex_value = self.general_parser.consume_while(is_valid_identifier_char);
-        let (r, g, b) = convert_hex_to_rgb(hex_value.as_str());
-        return DeclarationValue::Color(ColorValue { r, g, b, a: 255 });
+    // Reports the error, then skips the rest of the declaration up to and including the next ';'.
+    // The '}' closing the block is left for the caller.
+    fn drop_invalid_declaration(&mut self, dropped: DroppedConstruct, error: ParseError, in_block: bool) {
+        self.warnings.push(ParseWarning { dropped, error });
+        self.consume_component_values_until(|token| *token == Token::Semicolon || (in_block && *token == Token::CloseCurly));
+
+        if *self.peek_token() == Token::Semicolon {
+            self.pop_token();
+        }
     }
 
-    pub fn parse_length(&mut self) -> DeclarationValue {
-        let number = self.general_parser.consume_while(|c| {
-            match c {
-                '0' ..= '9' => true,
-                _ => false
+    // Consumes up to and including the '}' that closes the block whose '{' was just consumed.
+    fn consume_block_contents(&mut self) -> String {
+        let contents = self.consume_component_values_until(|token| *token == Token::CloseCurly);
+
+        if *self.peek_token() == Token::CloseCurly {
+            self.pop_token();
+        }
+
+        return String::from(contents.trim());
+    }
+
+    // Consumes component values until one starts with a token accepted by is_end, which is not consumed,
+    // and returns them serialized. Tokens inside nested blocks never end it.
+    fn consume_component_values_until<F>(&mut self, is_end: F) -> String
+        where F: Fn(&Token) -> bool
+    {
+        let mut output = String::new();
+
+        loop {
+            let token = self.peek_token();
+
+            if *token == Token::Eof || is_end(token) {
+                break;
             }
-        });
-        let number= number.parse::<f32>().unwrap();
-        let unit = self.general_parser.consume_while(is_valid_identifier_char).to_ascii_lowercase();
-        let unit = if unit == "px" { LengthUnit::Px } else { panic!() };
 
-        return DeclarationValue::Length(number, unit);
+            self.consume_component_value_into(&mut output);
+        }
+
+        return output;
+    }
+
+    // Consumes a single token, or a block with everything up to its matching closing token.
+    fn consume_component_value_into(&mut self, output: &mut String) {
+        let (token, _) = self.pop_token();
+        output.push_str(format!("{}", token).as_str());
+
+        let closing_token = match token {
+            Token::OpenCurly => Token::CloseCurly,
+            Token::OpenSquare => Token::CloseSquare,
+            Token::OpenParen | Token::Function(_) => Token::CloseParen,
+            _ => return
+        };
+
+        output.push_str(self.consume_component_values_until(|token| *token == closing_token).as_str());
+
+        if *self.peek_token() == closing_token {
+            self.pop_token();
+            output.push_str(format!("{}", closing_token).as_str());
+        }
     }
 
-    pub fn parse_keyword(&mut self) -> DeclarationValue {
-        let keyword = self.general_parser.consume_while(is_valid_identifier_char);
-        return DeclarationValue::Keyword(keyword);
+    // Creates the span from start_position to the end of the last consumed token, relative to the start of the file.
+    fn create_source_span(&self, start_position: usize) -> Option<SourceSpan> {
+        let source_span = self.tokenizer.get_source_span(self.file_id, start_position..self.previous_token_end);
+        return Some(source_span.offset_by(self.start_line, self.start_column));
     }
 }
 
-fn is_valid_identifier_char(char: char) -> bool {
-    match char {
-        '0' ..= '9' | 'a' ..= 'z' | 'A' ..= 'Z' | '_' | '-' => true,
-        _ => false
-    }
+// Whether the token starts a simple selector: a tag name, an id, a class or '*'.
+fn is_compound_selector_start(token: &Token) -> bool {
+    return matches!(token, Token::Ident(_) | Token::Hash { is_id: true, .. } | Token::Delim('.' | '*'));
 }
 
-fn convert_hex_to_rgb(hex: &str) -> (u8, u8, u8) {
-    (u8::from_str_radix(&hex[0 .. 2], 16).unwrap(),
-     u8::from_str_radix(&hex[2 .. 4], 16).unwrap(),
-     u8::from_str_radix(&hex[4 .. 6], 16).unwrap())
+fn convert_hex_to_rgb(hex: &str) -> Option<(u8, u8, u8)> {
+    if hex.len() != 6 || !hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some((u8::from_str_radix(&hex[0 .. 2], 16).ok()?,
+          u8::from_str_radix(&hex[2 .. 4], 16).ok()?,
+          u8::from_str_radix(&hex[4 .. 6], 16).ok()?))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::css_parser::{convert_hex_to_rgb, CSSParser};
-    use crate::general_parser::GeneralParser;
+    use crate::css_parser::dropped_construct::DroppedConstruct;
+    use crate::parse_error::ParseErrorKind;
 
     #[test]
     fn test_convert_hex_to_rgb() {
-        assert_eq!((250, 128, 114), convert_hex_to_rgb("fa8072"));
+        assert_eq!(Some((250, 128, 114)), convert_hex_to_rgb("fa8072"));
+        assert_eq!(None, convert_hex_to_rgb("fa80"));
+        assert_eq!(None, convert_hex_to_rgb("zz8072"));
+        assert_eq!(None, convert_hex_to_rgb("fa80ℝ"));
     }
 
     #[test]
     fn test_parse() {
-        let mut css_parser = CSSParser {
-            general_parser: GeneralParser {
-                content: "h1, h2, h3 { margin: auto; color: #cc0000; } div.note { margin-bottom: 20px; padding: 10px; } #answer { display: none; }".parse().unwrap(),
-                current_position: 0
-            }
-        };
+        let mut css_parser = CSSParser::new("h1, h2, h3 { margin: auto; color: #cc0000; } div.note { margin-bottom: 20px; padding: 10px; } #answer { display: none; }".parse().unwrap());
 
         let stylesheet = css_parser.parse();
         assert_eq!("h1, h2, h3 { margin: auto; color: #rgba(204, 0, 0, 255); }
 div.note { margin-bottom: 20px; padding: 10px; }
 #answer { display: none; }", format!("{}", stylesheet));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_combinators() {
+        let mut css_parser = CSSParser::new(String::from("nav  ul>li a.active , #main > .note{display:none}"));
+        let stylesheet = css_parser.parse();
+        assert_eq!("nav ul > li a.active, #main > .note { display: none; }", format!("{}", stylesheet));
+
+        let selector = &stylesheet.rules[0].selectors[0];
+        assert_eq!((0, 1, 4), selector.get_specificity());
+        assert_eq!("0:1:1-1:20", format!("{}", selector.source_span.unwrap()));
+
+        let mut css_parser = CSSParser::new(String::from("label+input, h2 ~p.note,a~ b > c {display:none}"));
+        let stylesheet = css_parser.parse();
+        assert_eq!("label + input, a ~ b > c, h2 ~ p.note { display: none; }", format!("{}", stylesheet));
+        assert!(css_parser.get_warnings().is_empty());
+
+        assert_eq!(ParseErrorKind::UnexpectedCharacter('{'), CSSParser::new(String::from("h2 ~ {")).parse_selector_list().err().unwrap().kind);
+    }
+
+    #[test]
+    fn test_parse_escapes_and_signed_lengths() {
+        let mut css_parser = CSSParser::new(String::from("<!-- .a\\:b, #\\31 x, \\68 1 { margin-left: -4.5PX; margin-top: +.5px } -->"));
+        let stylesheet = css_parser.parse();
+
+        assert_eq!("h1, .a:b, #1x { margin-left: -4.5px; margin-top: 0.5px; }", format!("{}", stylesheet));
+        assert!(css_parser.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parse_comments_and_at_rules() {
+        let mut css_parser = CSSParser::new(String::from("/* header */ @charset \"utf-8\";
+@font-face { font-family: \"A}\"; /* } */ src: url(a.woff) }
+h1, /* second */ h2 /* before block */ { /* first */ color: red /* value */; }
+@MEDIA screen /* comment */ and (min-width: 10px) { p { display: none; } }
+@import url(\"x.css\")"));
+
+        let stylesheet = css_parser.parse();
+        assert_eq!("h1, h2 { color: red; }", format!("{}", stylesheet));
+
+        let at_rules: Vec<String> = stylesheet.at_rules.iter().map(|at_rule| format!("{}", at_rule)).collect();
+        assert_eq!(vec![
+            "@charset \"utf-8\";",
+            "@font-face { font-family: \"A}\";  src: url(a.woff) }",
+            "@media screen  and (min-width: 10px) { p { display: none; } }",
+            "@import url(\"x.css\");"
+        ], at_rules);
+        assert_eq!("0:2:1-2:59", format!("{}", stylesheet.at_rules[1].source_span.unwrap()));
+
+        let mut css_parser = CSSParser::new(String::from("@media print { p { color: red; }"));
+        assert_eq!(Some(String::from("p { color: red; }")), css_parser.parse().at_rules[0].block);
+
+        let mut css_parser = CSSParser::new(String::from("/* unterminated"));
+        assert!(css_parser.parse().rules.is_empty());
+    }
+
+    #[test]
+    fn test_parse_reports_warnings_with_position() {
+        let cases = [
+            ("h1 { margin: 2em; }", "h1 { }", DroppedConstruct::Declaration, ParseErrorKind::InvalidLengthUnit(String::from("em")), (1, 14)),
+            ("h1 {\n  color: #ggg000;\n}", "h1 { }", DroppedConstruct::Declaration, ParseErrorKind::InvalidColor(String::from("ggg000")), (2, 10)),
+            ("h1 { color: red !ie; display: none }", "h1 { display: none; }", DroppedConstruct::Declaration, ParseErrorKind::UnexpectedCharacter('!'), (1, 17)),
+            ("h1 { margin: 1; display: none }", "h1 { display: none; }", DroppedConstruct::Declaration, ParseErrorKind::InvalidLengthUnit(String::new()), (1, 14)),
+            ("h1 { color: red ! ie; display: none }", "h1 { display: none; }", DroppedConstruct::Declaration, ParseErrorKind::UnexpectedCharacter('!'), (1, 17)),
+            ("h1 { color: }", "h1 { }", DroppedConstruct::Declaration, ParseErrorKind::ExpectedIdentifier, (1, 13)),
+            ("h1 { @media print { p { color: red; } } display: none; }", "h1 { display: none; }", DroppedConstruct::AtRule, ParseErrorKind::UnexpectedAtRule(String::from("media")), (1, 6)),
+            ("h1 ! p { color: red; } p { display: none; }", "p { display: none; }", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('!'), (1, 4)),
+            ("ul > { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('{'), (1, 6)),
+            ("> p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('>'), (1, 1)),
+            ("a, > p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('>'), (1, 4)),
+            ("+ p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('+'), (1, 1)),
+            ("~ p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('~'), (1, 1)),
+            ("label + { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('{'), (1, 9)),
+            ("h2 ~ ~ p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('~'), (1, 6)),
+            ("h1", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedEof, (1, 3)),
+            ("@ { } p { display: none; }", "p { display: none; }", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('@'), (1, 1)),
+            ("{ color: red } p { display: none; }", "p { display: none; }", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('{'), (1, 1)),
+            (", p { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter(','), (1, 1)),
+            ("p, { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter('{'), (1, 4)),
+            ("p,, a { color: red; }", "", DroppedConstruct::Rule, ParseErrorKind::UnexpectedCharacter(','), (1, 3)),
+        ];
+
+        for (content, expected_stylesheet, dropped, kind, (line, column)) in cases {
+            let mut css_parser = CSSParser::new(String::from(content));
+            let stylesheet = css_parser.parse();
+            assert_eq!(expected_stylesheet, format!("{}", stylesheet));
+
+            let warnings = css_parser.get_warnings();
+            assert_eq!(1, warnings.len(), "{}", content);
+            assert_eq!(dropped, warnings[0].dropped);
+            assert_eq!(kind, warnings[0].error.kind);
+            assert_eq!((line, column), (warnings[0].error.line, warnings[0].error.column));
+        }
+    }
+
+    #[test]
+    fn test_parse_recovers_from_errors() {
+        let mut css_parser = CSSParser::new(String::from("h1 { color: red }
+h2 { margin: 0px; filter: alpha(opacity=50; x: y); color: #ff0000 }
+a:hover { display: none; }
+p { color: \"};\" ; margin: 2em [ ; } ] ; display: block }
+div { display: none; }"));
+
+        let stylesheet = css_parser.parse();
+        assert_eq!("h1 { color: red; }
+h2 { margin: 0px; color: #rgba(255, 0, 0, 255); }
+p { display: block; }
+div { display: none; }", format!("{}", stylesheet));
+
+        let warnings: Vec<String> = css_parser.get_warnings().iter().map(|warning| format!("{}", warning)).collect();
+        assert_eq!(vec![
+            "dropped invalid declaration: expected an identifier at line 2, column 27",
+            "dropped invalid rule: unexpected character ':' at line 3, column 2",
+            "dropped invalid declaration: expected an identifier at line 4, column 12",
+            "dropped invalid declaration: unsupported length unit 'em' at line 4, column 27"
+        ], warnings);
+    }
+
+    #[test]
+    fn test_parse_records_source_spans() {
+        let mut css_parser = CSSParser::new(String::from("h1,\n.note { color: red;\n  margin: 0px; }"));
+        css_parser.set_source_location(2, 10, 8);
+        let stylesheet = css_parser.parse();
+        let rule = &stylesheet.rules[0];
+
+        assert_eq!("2:10:8-12:17", format!("{}", rule.source_span.unwrap()));
+
+        let selector_spans: Vec<String> = rule.selectors.iter().map(|selector| format!("{}", selector.source_span.unwrap())).collect();
+        assert_eq!(vec!["2:10:8-10:10", "2:11:1-11:6"], selector_spans);
+
+        let declaration_spans: Vec<String> = rule.declarations.iter().map(|declaration| format!("{}", declaration.source_span.unwrap())).collect();
+        assert_eq!(vec!["2:11:9-11:19", "2:12:3-12:14"], declaration_spans);
+    }
+
+    #[test]
+    fn test_parse_inline_declarations() {
+        let mut css_parser = CSSParser::new(String::from(" color: #ff0000; margin-left : 4px;display:none "));
+        let declarations = css_parser.parse_inline_declarations();
+        let declarations: Vec<String> = declarations.iter().map(|declaration| format!("{}", declaration)).collect();
+        assert_eq!(vec!["color: #rgba(255, 0, 0, 255)", "margin-left: 4px", "display: none"], declarations);
+
+        let mut css_parser = CSSParser::new(String::from("color: #ff0000; ; margin: 1em; } display: none"));
+        let declarations: Vec<String> = css_parser.parse_inline_declarations().iter().map(|declaration| format!("{}", declaration)).collect();
+        assert_eq!(vec!["color: #rgba(255, 0, 0, 255)"], declarations);
+
+        let warnings: Vec<ParseErrorKind> = css_parser.get_warnings().iter().map(|warning| warning.error.kind.clone()).collect();
+        assert_eq!(vec![ParseErrorKind::InvalidLengthUnit(String::from("em")), ParseErrorKind::ExpectedIdentifier], warnings);
+
+        let mut css_parser = CSSParser::new(String::new());
+        assert!(css_parser.parse_inline_declarations().is_empty());
+    }
+
+    #[test]
+    fn test_parse_declaration_with_zero_length_and_important() {
+        let mut css_parser = CSSParser::new(String::from("margin: 0; padding-left: -0 ; color: red !important; display: none ! IMPORTANT ;width:0px!important"));
+        let declarations: Vec<String> = css_parser.parse_inline_declarations().iter().map(|declaration| format!("{}", declaration)).collect();
+        assert_eq!(vec!["margin: 0px", "padding-left: 0px", "color: red", "display: none", "width: 0px"], declarations);
+        assert!(css_parser.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parse_selector_list() {
+        let mut css_parser = CSSParser::new(String::from(" div.note, #main "));
+        let selectors: Vec<String> = css_parser.parse_selector_list().unwrap().iter().map(|selector| format!("{}", selector)).collect();
+        assert_eq!(vec!["div.note", "#main"], selectors);
+
+        let mut css_parser = CSSParser::new(String::from("div { color: red; }"));
+        assert_eq!(ParseErrorKind::UnexpectedCharacter('{'), css_parser.parse_selector_list().err().unwrap().kind);
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::{Display, Formatter};
+use crate::source_span::SourceSpan;
+
+// An at-rule such as @charset, @media or @font-face. None of them are interpreted yet,
+// so the prelude and the block are kept as written.
+pub struct AtRule {
+    // Without the '@'.
+    pub(crate) name: String,
+    // Everything between the name and the block or semicolon, without comments.
+    pub(crate) prelude: String,
+    // The text between the braces, or None if the at-rule ends with a semicolon.
+    pub(crate) block: Option<String>,
+    pub(crate) source_span: Option<SourceSpan>
+}
+
+impl Display for AtRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@{}", self.name)?;
+
+        if !self.prelude.is_empty() {
+            write!(f, " {}", self.prelude)?;
+        }
+
+        return match &self.block {
+            Some(block) => write!(f, " {{ {} }}", block),
+            None => write!(f, ";")
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css_parser::at_rule::AtRule;
+
+    #[test]
+    fn test_display() {
+        let at_rule = AtRule { name: String::from("charset"), prelude: String::from("\"utf-8\""), block: None, source_span: None };
+        assert_eq!("@charset \"utf-8\";", format!("{}", at_rule));
+
+        let at_rule = AtRule { name: String::from("font-face"), prelude: String::new(), block: Some(String::from("font-family: x;")), source_span: None };
+        assert_eq!("@font-face { font-family: x; }", format!("{}", at_rule));
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt::{Display, Formatter};
+
+// How a compound selector relates to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combinator {
+    // Written as whitespace: the element is anywhere inside an element matching the previous selector.
+    Descendant,
+    // Written as '>': the element is a child of an element matching the previous selector.
+    Child,
+    // Written as '+': the element directly follows an element matching the previous selector.
+    NextSibling,
+    // Written as '~': the element follows an element matching the previous selector, with the same parent.
+    SubsequentSibling
+}
+
+impl Display for Combinator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combinator::Descendant => write!(f, " "),
+            Combinator::Child => write!(f, " > "),
+            Combinator::NextSibling => write!(f, " + "),
+            Combinator::SubsequentSibling => write!(f, " ~ ")
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use std::fmt::{Display, Formatter};
 use crate::css_parser::declaration_value::DeclarationValue;
+use crate::source_span::SourceSpan;
 
 pub struct Declaration {
     pub(crate) name: String,
-    pub(crate) value: DeclarationValue
+    pub(crate) value: DeclarationValue,
+    // From the property name to the end of the value, without the semicolon.
+    pub(crate) source_span: Option<SourceSpan>
 }
 
 impl Display for Declaration {
@@ -23,17 +26,20 @@ mod tests {
     fn test_display() {
         let keyword = Declaration {
             name: String::from("display"),
-            value: DeclarationValue::Keyword(String::from("none"))
+            value: DeclarationValue::Keyword(String::from("none")),
+            source_span: None
         };
 
         let length = Declaration {
             name: String::from("margin-bottom"),
-            value: DeclarationValue::Length(20.2, LengthUnit::Px)
+            value: DeclarationValue::Length(20.2, LengthUnit::Px),
+            source_span: None
         };
 
         let color = Declaration {
             name: String::from("color"),
-            value: DeclarationValue::Color(ColorValue{r: 255, g: 99, b: 71, a: 1})
+            value: DeclarationValue::Color(ColorValue{r: 255, g: 99, b: 71, a: 1}),
+            source_span: None
         };
 
         assert_eq!("display: none", format!("{}", keyword));
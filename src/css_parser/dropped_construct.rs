@@ -0,0 +1,19 @@
+use std::fmt::{Display, Formatter};
+
+// What the parser threw away when it recovered from an error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DroppedConstruct {
+    Rule,
+    AtRule,
+    Declaration
+}
+
+impl Display for DroppedConstruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DroppedConstruct::Rule => write!(f, "rule"),
+            DroppedConstruct::AtRule => write!(f, "at-rule"),
+            DroppedConstruct::Declaration => write!(f, "declaration")
+        }
+    }
+}
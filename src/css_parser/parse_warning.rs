@@ -0,0 +1,33 @@
+use std::fmt::{Display, Formatter};
+use crate::css_parser::dropped_construct::DroppedConstruct;
+use crate::parse_error::ParseError;
+
+// An error the parser recovered from by dropping a construct, as CSS requires.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    pub dropped: DroppedConstruct,
+    pub error: ParseError
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dropped invalid {}: {}", self.dropped, self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css_parser::dropped_construct::DroppedConstruct;
+    use crate::css_parser::parse_warning::ParseWarning;
+    use crate::parse_error::{ParseError, ParseErrorKind};
+
+    #[test]
+    fn test_display() {
+        let warning = ParseWarning {
+            dropped: DroppedConstruct::Declaration,
+            error: ParseError { kind: ParseErrorKind::InvalidLengthUnit(String::from("em")), line: 2, column: 11, span: 20..22 }
+        };
+
+        assert_eq!("dropped invalid declaration: unsupported length unit 'em' at line 2, column 11", format!("{}", warning));
+    }
+}
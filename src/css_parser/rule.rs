@@ -1,10 +1,13 @@
 use std::fmt::{Display, Formatter};
 use crate::css_parser::declaration::Declaration;
 use crate::css_parser::selector::Selector;
+use crate::source_span::SourceSpan;
 
 pub struct Rule {
     pub(crate) selectors: Vec<Selector>,
-    pub(crate) declarations: Vec<Declaration>
+    pub(crate) declarations: Vec<Declaration>,
+    // From the first selector to the closing brace.
+    pub(crate) source_span: Option<SourceSpan>
 }
 
 impl Display for Rule {
@@ -43,40 +46,50 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("h1")),
                 id: None,
-                classes: vec![]
+                classes: vec![],
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: Some(String::from("h2")),
                 id: None,
-                classes: vec![]
+                classes: vec![],
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: Some(String::from("h3")),
                 id: None,
-                classes: vec![]
+                classes: vec![],
+                combinator: None,
+                source_span: None
             },
         ];
 
         let declarations = vec![
             Declaration {
                 name: String::from("display"),
-                value: DeclarationValue::Keyword(String::from("none"))
+                value: DeclarationValue::Keyword(String::from("none")),
+                source_span: None
             },
             Declaration {
                 name: String::from("margin-bottom"),
-                value: DeclarationValue::Length(20.2, LengthUnit::Px)
+                value: DeclarationValue::Length(20.2, LengthUnit::Px),
+                source_span: None
             },
             Declaration {
                 name: String::from("color"),
-                value: DeclarationValue::Color(ColorValue{r: 255, g: 99, b: 71, a: 1})
+                value: DeclarationValue::Color(ColorValue{r: 255, g: 99, b: 71, a: 1}),
+                source_span: None
             }
         ];
 
         let rule = Rule {
             selectors,
-            declarations
+            declarations,
+            source_span: None
         };
 
-        assert_eq!("h1, h2, h3,  {display: none; margin-bottom: 20.2px; color: #rgba(255, 99, 71, 1); }", format!("{}", rule));
+        assert_eq!("h1, h2, h3 { display: none; margin-bottom: 20.2px; color: #rgba(255, 99, 71, 1); }", format!("{}", rule));
     }
 }
\ No newline at end of file
@@ -1,19 +1,44 @@
 use std::fmt::{Display, Formatter};
+use crate::css_parser::combinator::Combinator;
+use crate::source_span::SourceSpan;
 
+// A compound selector, such as div.note, and the selectors before it if it is the last
+// compound of a complex selector such as nav > ul a.
 pub struct Selector {
     pub(crate) tag_name: Option<String>,
     pub(crate) id: Option<String>,
-    pub(crate) classes: Vec<String>
+    pub(crate) classes: Vec<String>,
+    // The selector before this compound and how the two relate, so that matching goes from right to left.
+    pub(crate) combinator: Option<(Combinator, Box<Selector>)>,
+    // The whole complex selector up to and including this compound.
+    pub(crate) source_span: Option<SourceSpan>
 }
 
 impl Selector {
+    // Sums the ids, classes and tag names of all compounds.
     pub fn get_specificity(&self) -> (usize, usize, usize) {
-        return (self.id.iter().count(), self.classes.len(), self.tag_name.iter().count());
+        let specificity = (self.id.iter().count(), self.classes.len(), self.tag_name.iter().count());
+
+        return match &self.combinator {
+            Some((_, previous_selector)) => {
+                let (ids, classes, tag_names) = previous_selector.get_specificity();
+                (specificity.0 + ids, specificity.1 + classes, specificity.2 + tag_names)
+            },
+            None => specificity
+        };
     }
 }
 
 impl Display for Selector {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((combinator, previous_selector)) = &self.combinator {
+            write!(f, "{}{}", previous_selector, combinator)?;
+        }
+
+        if self.tag_name.is_none() && self.id.is_none() && self.classes.is_empty() {
+            return write!(f, "*");
+        }
+
         write!(f,
                "{}{}{}",
                if let Some(tag_name) = &self.tag_name {
@@ -37,14 +62,41 @@ impl Display for Selector {
 
 #[cfg(test)]
 mod tests {
+    use crate::css_parser::combinator::Combinator;
     use crate::css_parser::selector::Selector;
 
+    #[test]
+    fn test_get_specificity() {
+        let create_selector = |tag_name: Option<&str>, id: Option<&str>, classes: Vec<&str>, combinator: Option<(Combinator, Selector)>| Selector {
+            tag_name: tag_name.map(String::from),
+            id: id.map(String::from),
+            classes: classes.into_iter().map(String::from).collect(),
+            combinator: combinator.map(|(combinator, selector)| (combinator, Box::new(selector))),
+            source_span: None
+        };
+        let nav = create_selector(Some("nav"), Some("main"), Vec::new(), None);
+        let list = create_selector(Some("ul"), None, vec!["menu"], Some((Combinator::Descendant, nav)));
+        let link = create_selector(Some("a"), None, vec!["active", "external"], Some((Combinator::Child, list)));
+
+        assert_eq!((1, 3, 3), link.get_specificity());
+        assert_eq!("nav#main ul.menu > a.active.external", format!("{}", link));
+        assert_eq!("* > a", format!("{}", create_selector(Some("a"), None, Vec::new(), Some((Combinator::Child, create_selector(None, None, Vec::new(), None))))));
+
+        let label = create_selector(Some("label"), None, Vec::new(), None);
+        let input = create_selector(Some("input"), None, Vec::new(), Some((Combinator::NextSibling, label)));
+        let paragraph = create_selector(Some("p"), None, Vec::new(), Some((Combinator::SubsequentSibling, input)));
+        assert_eq!((0, 0, 3), paragraph.get_specificity());
+        assert_eq!("label + input ~ p", format!("{}", paragraph));
+    }
+
     #[test]
     fn test_display() {
         let selector = Selector {
             tag_name: Some(String::from("div")),
             id: Some(String::from("answer")),
-            classes: vec![String::from("note"), String::from("button"), String::from("topbar")]
+            classes: vec![String::from("note"), String::from("button"), String::from("topbar")],
+            combinator: None,
+            source_span: None
         };
 
         println!("{}", selector);
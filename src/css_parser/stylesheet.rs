@@ -1,19 +1,16 @@
-use std::fmt::{Display, Formatter, Pointer, write};
+use std::fmt::{Display, Formatter};
+use crate::css_parser::at_rule::AtRule;
 use crate::css_parser::rule::Rule;
 
 pub struct Stylesheet {
-    pub(crate) rules: Vec<Rule>
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) at_rules: Vec<AtRule>
 }
 
 impl Display for Stylesheet {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut string = self.rules.iter().fold(String::from(""), |mut acc, x| {
-            acc.push_str(format!("{}", x).as_str());
-            acc.push_str("\n");
-            return acc;
-        });
-        let string = string.split_at(string.len() - 1).0;
+        let rules: Vec<String> = self.rules.iter().map(|rule| format!("{}", rule)).collect();
 
-        write!(f, "{}", string)
+        write!(f, "{}", rules.join("\n"))
     }
-}
\ No newline at end of file
+}
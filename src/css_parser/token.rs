@@ -0,0 +1,85 @@
+use std::fmt::{Display, Formatter};
+
+// The tokens of CSS Syntax Level 3. Names, strings and URLs have their escapes decoded,
+// and comments are dropped by the tokenizer.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token {
+    Ident(String),
+    // A name directly followed by '(', which is part of the token.
+    Function(String),
+    AtKeyword(String),
+    Hash {
+        value: String,
+        // Set if the value is a valid identifier, as an id selector requires.
+        is_id: bool
+    },
+    String(String),
+    // A string with an unescaped newline in it.
+    BadString,
+    // An unquoted url(...); a quoted one is a Function token followed by a String token.
+    Url(String),
+    BadUrl,
+    Number(f32),
+    Percentage(f32),
+    Dimension(f32, String),
+    UnicodeRange(u32, u32),
+    Whitespace,
+    // "<!--" and "-->", which are allowed around rules for old browsers that showed <style> content.
+    Cdo,
+    Cdc,
+    Colon,
+    Semicolon,
+    Comma,
+    OpenSquare,
+    CloseSquare,
+    OpenParen,
+    CloseParen,
+    OpenCurly,
+    CloseCurly,
+    Delim(char),
+    Eof
+}
+
+impl Token {
+    // Returns the character of a token that is always a single character, such as Comma or Delim.
+    pub fn get_char(&self) -> Option<char> {
+        return match self {
+            Token::Colon => Some(':'),
+            Token::Semicolon => Some(';'),
+            Token::Comma => Some(','),
+            Token::OpenSquare => Some('['),
+            Token::CloseSquare => Some(']'),
+            Token::OpenParen => Some('('),
+            Token::CloseParen => Some(')'),
+            Token::OpenCurly => Some('{'),
+            Token::CloseCurly => Some('}'),
+            Token::Delim(char) => Some(*char),
+            _ => None
+        };
+    }
+}
+
+// Serializes the token back to CSS. A run of whitespace becomes a single space,
+// and names and strings are not escaped again.
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Function(name) => write!(f, "{}(", name),
+            Token::AtKeyword(name) => write!(f, "@{}", name),
+            Token::Hash { value, .. } => write!(f, "#{}", value),
+            Token::String(value) => write!(f, "\"{}\"", value),
+            Token::Url(url) => write!(f, "url({})", url),
+            Token::BadString | Token::BadUrl | Token::Eof => Ok(()),
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Percentage(value) => write!(f, "{}%", value),
+            Token::Dimension(value, unit) => write!(f, "{}{}", value, unit),
+            Token::UnicodeRange(start, end) if start == end => write!(f, "U+{:X}", start),
+            Token::UnicodeRange(start, end) => write!(f, "U+{:X}-{:X}", start, end),
+            Token::Whitespace => write!(f, " "),
+            Token::Cdo => write!(f, "<!--"),
+            Token::Cdc => write!(f, "-->"),
+            token => write!(f, "{}", token.get_char().unwrap())
+        }
+    }
+}
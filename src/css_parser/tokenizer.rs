@@ -0,0 +1,519 @@
+use std::ops::Range;
+use crate::css_parser::token::Token;
+use crate::general_parser::GeneralParser;
+use crate::parse_error::{ParseError, ParseErrorKind};
+use crate::source_span::SourceSpan;
+
+// Splits CSS into tokens as described in CSS Syntax Level 3. Tokenizing never fails:
+// malformed input becomes BadString, BadUrl or Delim tokens for the parser to deal with.
+pub struct Tokenizer {
+    general_parser: GeneralParser
+}
+
+impl Tokenizer {
+    pub fn new(content: String) -> Self {
+        Self {
+            general_parser: GeneralParser::new(content)
+        }
+    }
+
+    // Returns the next token with its byte range. At the end of the input, Eof is returned every time.
+    pub fn next_token(&mut self) -> (Token, Range<usize>) {
+        self.consume_comments();
+        let start_position = self.general_parser.get_position();
+        let token = self.consume_token();
+
+        return (token, start_position..self.general_parser.get_position());
+    }
+
+    // Returns the span of a byte range. Locating a range close to the current position is cheap,
+    // so the spans of tokens should be requested soon after they are read.
+    pub fn get_source_span(&self, file_id: usize, range: Range<usize>) -> SourceSpan {
+        return self.general_parser.get_source_span(file_id, range);
+    }
+
+    pub fn create_error(&self, kind: ParseErrorKind, range: Range<usize>) -> ParseError {
+        let (line, column) = self.general_parser.get_line_and_column(range.start);
+
+        return ParseError {
+            kind,
+            line,
+            column,
+            span: range
+        };
+    }
+
+    // An unterminated comment runs to the end of the input.
+    fn consume_comments(&mut self) {
+        while self.general_parser.consume_str("/*") {
+            self.general_parser.consume_until("*/");
+            self.general_parser.consume_str("*/");
+        }
+    }
+
+    fn consume_token(&mut self) -> Token {
+        if self.general_parser.is_eof() {
+            return Token::Eof;
+        }
+
+        let current_char = self.general_parser.peek_current_char();
+
+        if is_whitespace(current_char) {
+            self.general_parser.consume_while(is_whitespace);
+            return Token::Whitespace;
+        }
+
+        if current_char.is_ascii_digit() {
+            return self.consume_numeric_token();
+        }
+
+        if is_identifier_start_char(current_char) {
+            if (current_char == 'u' || current_char == 'U') && self.is_unicode_range_start() {
+                return self.consume_unicode_range_token();
+            }
+
+            return self.consume_ident_like_token();
+        }
+
+        self.general_parser.pop_current_char();
+
+        return match current_char {
+            '"' | '\'' => self.consume_string_token(current_char),
+            '#' if self.peek(0).is_some_and(is_identifier_char) || self.is_valid_escape(0) => {
+                let is_id = self.would_start_identifier(0);
+                Token::Hash { value: self.consume_name(), is_id }
+            },
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            '[' => Token::OpenSquare,
+            ']' => Token::CloseSquare,
+            '{' => Token::OpenCurly,
+            '}' => Token::CloseCurly,
+            '+' | '.' if self.would_start_number(current_char) => self.consume_numeric_token_after(current_char),
+            '-' if self.would_start_number(current_char) => self.consume_numeric_token_after(current_char),
+            '-' if self.general_parser.consume_str("->") => Token::Cdc,
+            '-' if self.peek(0) == Some('-') || self.peek(0).is_some_and(is_identifier_start_char) || self.is_valid_escape(0) => {
+                let name = self.consume_name_after(String::from("-"));
+                self.consume_ident_like_token_named(name)
+            },
+            '<' if self.general_parser.consume_str("!--") => Token::Cdo,
+            '@' if self.would_start_identifier(0) => Token::AtKeyword(self.consume_name()),
+            '\\' if self.peek(0).is_some_and(|char| !is_newline(char)) => {
+                let name = String::from(self.consume_escape());
+                let name = self.consume_name_after(name);
+                self.consume_ident_like_token_named(name)
+            },
+            char => Token::Delim(char)
+        };
+    }
+
+    fn consume_ident_like_token(&mut self) -> Token {
+        let name = self.consume_name();
+        return self.consume_ident_like_token_named(name);
+    }
+
+    // Decides between Ident, Function and Url once the name has been consumed.
+    fn consume_ident_like_token_named(&mut self, name: String) -> Token {
+        if !self.general_parser.consume_str("(") {
+            return Token::Ident(name);
+        }
+
+        if !name.eq_ignore_ascii_case("url") {
+            return Token::Function(name);
+        }
+
+        // A quoted URL is a function, so that its string is read as a String token.
+        while self.peek(0).is_some_and(is_whitespace) && self.peek(1).is_some_and(is_whitespace) {
+            self.general_parser.pop_current_char();
+        }
+
+        return match (self.peek(0), self.peek(1)) {
+            (Some('"' | '\''), _) => Token::Function(name),
+            (Some(char), Some('"' | '\'')) if is_whitespace(char) => Token::Function(name),
+            _ => self.consume_url_token()
+        };
+    }
+
+    fn consume_url_token(&mut self) -> Token {
+        let mut url = String::new();
+        self.general_parser.consume_while(is_whitespace);
+
+        while !self.general_parser.is_eof() {
+            url.push_str(self.general_parser.consume_while(|char| {
+                !is_whitespace(char) && !matches!(char, ')' | '"' | '\'' | '(' | '\\') && !is_non_printable(char)
+            }));
+
+            if self.general_parser.is_eof() {
+                break;
+            }
+
+            match self.general_parser.peek_current_char() {
+                ')' => {
+                    self.general_parser.pop_current_char();
+                    break;
+                },
+                char if is_whitespace(char) => {
+                    self.general_parser.consume_while(is_whitespace);
+
+                    if self.general_parser.is_eof() || self.general_parser.consume_str(")") {
+                        break;
+                    }
+
+                    self.consume_bad_url_remnants();
+                    return Token::BadUrl;
+                },
+                '\\' if self.is_valid_escape(0) => {
+                    self.general_parser.pop_current_char();
+                    url.push(self.consume_escape());
+                },
+                _ => {
+                    self.consume_bad_url_remnants();
+                    return Token::BadUrl;
+                }
+            }
+        }
+
+        return Token::Url(url);
+    }
+
+    // Skips the rest of a malformed URL up to its ')', so that parsing can continue after it.
+    fn consume_bad_url_remnants(&mut self) {
+        while !self.general_parser.is_eof() {
+            if self.is_valid_escape(0) {
+                self.general_parser.pop_current_char();
+                self.consume_escape();
+            } else if self.general_parser.pop_current_char() == ')' {
+                break;
+            }
+        }
+    }
+
+    // The opening quote has been consumed. A string ends at its closing quote or the end of
+    // the input; an unescaped newline makes it a BadString and is left for the next token.
+    fn consume_string_token(&mut self, quote: char) -> Token {
+        let mut value = String::new();
+
+        loop {
+            value.push_str(self.general_parser.consume_while(|char| char != quote && char != '\\' && !is_newline(char)));
+
+            if self.general_parser.is_eof() {
+                break;
+            }
+
+            match self.general_parser.peek_current_char() {
+                '\\' => {
+                    self.general_parser.pop_current_char();
+
+                    match self.peek(0) {
+                        None => {},
+                        // An escaped newline continues the string on the next line.
+                        Some(char) if is_newline(char) => {
+                            self.general_parser.pop_current_char();
+                        },
+                        Some(_) => value.push(self.consume_escape())
+                    }
+                },
+                char if char == quote => {
+                    self.general_parser.pop_current_char();
+                    break;
+                },
+                _ => return Token::BadString
+            }
+        }
+
+        return Token::String(value);
+    }
+
+    fn consume_numeric_token(&mut self) -> Token {
+        return self.consume_numeric_token_after_repr(String::new());
+    }
+
+    // Used when the sign or the leading '.' has already been consumed.
+    fn consume_numeric_token_after(&mut self, first_char: char) -> Token {
+        return self.consume_numeric_token_after_repr(String::from(first_char));
+    }
+
+    fn consume_numeric_token_after_repr(&mut self, mut repr: String) -> Token {
+        repr.push_str(self.general_parser.consume_while(|char| char.is_ascii_digit()));
+
+        if !repr.contains('.') && self.peek(0) == Some('.') && self.peek(1).is_some_and(|char| char.is_ascii_digit()) {
+            self.general_parser.pop_current_char();
+            repr.push('.');
+            repr.push_str(self.general_parser.consume_while(|char| char.is_ascii_digit()));
+        }
+
+        let has_exponent = match (self.peek(0), self.peek(1), self.peek(2)) {
+            (Some('e' | 'E'), Some(char), _) if char.is_ascii_digit() => true,
+            (Some('e' | 'E'), Some('+' | '-'), Some(char)) if char.is_ascii_digit() => true,
+            _ => false
+        };
+
+        if has_exponent {
+            repr.push(self.general_parser.pop_current_char());
+            repr.push(self.general_parser.pop_current_char());
+            repr.push_str(self.general_parser.consume_while(|char| char.is_ascii_digit()));
+        }
+
+        // The representation follows the grammar of a CSS number, which Rust accepts too,
+        // except for a leading '+' or '.' that parse() handles as well.
+        let value = repr.parse::<f32>().unwrap_or(0.0);
+
+        if self.would_start_identifier(0) {
+            return Token::Dimension(value, self.consume_name());
+        }
+
+        if self.general_parser.consume_str("%") {
+            return Token::Percentage(value);
+        }
+
+        return Token::Number(value);
+    }
+
+    fn is_unicode_range_start(&self) -> bool {
+        return self.peek(1) == Some('+') && self.peek(2).is_some_and(|char| char.is_ascii_hexdigit() || char == '?');
+    }
+
+    // Reads "U+" followed by a hex number, a range of two hex numbers, or a hex number ending in
+    // '?' wildcards such as U+4??, which covers U+400 to U+4FF.
+    fn consume_unicode_range_token(&mut self) -> Token {
+        self.general_parser.pop_current_char();
+        self.general_parser.pop_current_char();
+
+        let mut digits = self.consume_hex_digits(6);
+
+        while digits.len() < 6 && self.general_parser.consume_str("?") {
+            digits.push('?');
+        }
+
+        let start = u32::from_str_radix(digits.replace('?', "0").as_str(), 16).unwrap_or(0);
+
+        if digits.contains('?') {
+            return Token::UnicodeRange(start, u32::from_str_radix(digits.replace('?', "F").as_str(), 16).unwrap_or(0));
+        }
+
+        if self.peek(0) == Some('-') && self.peek(1).is_some_and(|char| char.is_ascii_hexdigit()) {
+            self.general_parser.pop_current_char();
+            let end = u32::from_str_radix(self.consume_hex_digits(6).as_str(), 16).unwrap_or(0);
+            return Token::UnicodeRange(start, end);
+        }
+
+        return Token::UnicodeRange(start, start);
+    }
+
+    fn consume_hex_digits(&mut self, max_count: usize) -> String {
+        let mut digits = String::new();
+
+        while digits.len() < max_count && self.peek(0).is_some_and(|char| char.is_ascii_hexdigit()) {
+            digits.push(self.general_parser.pop_current_char());
+        }
+
+        return digits;
+    }
+
+    fn consume_name(&mut self) -> String {
+        return self.consume_name_after(String::new());
+    }
+
+    // Continues a name whose start has already been read.
+    fn consume_name_after(&mut self, mut name: String) -> String {
+        loop {
+            name.push_str(self.general_parser.consume_while(is_identifier_char));
+
+            if !self.is_valid_escape(0) {
+                break;
+            }
+
+            self.general_parser.pop_current_char();
+            name.push(self.consume_escape());
+        }
+
+        return name;
+    }
+
+    // The '\' has been consumed. Hex escapes may be followed by a single whitespace that ends them.
+    fn consume_escape(&mut self) -> char {
+        if self.general_parser.is_eof() {
+            return char::REPLACEMENT_CHARACTER;
+        }
+
+        if !self.general_parser.peek_current_char().is_ascii_hexdigit() {
+            return self.general_parser.pop_current_char();
+        }
+
+        let code_point = u32::from_str_radix(self.consume_hex_digits(6).as_str(), 16).unwrap_or(0);
+
+        if self.peek(0).is_some_and(is_whitespace) {
+            self.general_parser.pop_current_char();
+        }
+
+        return match char::from_u32(code_point) {
+            Some('\0') | None => char::REPLACEMENT_CHARACTER,
+            Some(char) => char
+        };
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        return self.general_parser.peek_nth(n);
+    }
+
+    // Whether the n-th upcoming character is a '\' that starts an escape.
+    fn is_valid_escape(&self, n: usize) -> bool {
+        return self.peek(n) == Some('\\') && self.peek(n + 1).is_some_and(|char| !is_newline(char));
+    }
+
+    fn would_start_identifier(&self, n: usize) -> bool {
+        return match self.peek(n) {
+            Some('-') => {
+                self.peek(n + 1).is_some_and(|char| char == '-' || is_identifier_start_char(char)) || self.is_valid_escape(n + 1)
+            },
+            Some('\\') => self.is_valid_escape(n),
+            Some(char) => is_identifier_start_char(char),
+            None => false
+        };
+    }
+
+    // Whether a number starts with first_char, which has been consumed, and the upcoming characters.
+    fn would_start_number(&self, first_char: char) -> bool {
+        let is_digit = |n: usize| self.peek(n).is_some_and(|char| char.is_ascii_digit());
+
+        return match first_char {
+            '+' | '-' => is_digit(0) || (self.peek(0) == Some('.') && is_digit(1)),
+            '.' => is_digit(0),
+            _ => false
+        };
+    }
+}
+
+impl Iterator for Tokenizer {
+    type Item = Token;
+
+    // Stops at the end of the input instead of returning Eof.
+    fn next(&mut self) -> Option<Self::Item> {
+        return match self.next_token() {
+            (Token::Eof, _) => None,
+            (token, _) => Some(token)
+        };
+    }
+}
+
+fn is_whitespace(char: char) -> bool {
+    return matches!(char, ' ' | '\t' | '\n' | '\r' | '\x0C');
+}
+
+fn is_newline(char: char) -> bool {
+    return matches!(char, '\n' | '\r' | '\x0C');
+}
+
+fn is_identifier_start_char(char: char) -> bool {
+    return char.is_ascii_alphabetic() || char == '_' || !char.is_ascii();
+}
+
+fn is_identifier_char(char: char) -> bool {
+    return is_identifier_start_char(char) || char.is_ascii_digit() || char == '-';
+}
+
+fn is_non_printable(char: char) -> bool {
+    return matches!(char, '\0'..='\x08' | '\x0B' | '\x0E'..='\x1F' | '\x7F');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css_parser::token::Token;
+    use crate::css_parser::tokenizer::Tokenizer;
+
+    fn tokenize(content: &str) -> Vec<Token> {
+        return Tokenizer::new(String::from(content)).filter(|token| *token != Token::Whitespace).collect();
+    }
+
+    fn ident(name: &str) -> Token {
+        return Token::Ident(String::from(name));
+    }
+
+    #[test]
+    fn test_tokenize_rule() {
+        let tokens = Tokenizer::new(String::from("h1.a\\:b, #x /* c */{ margin:-1.5e1px }")).collect::<Vec<Token>>();
+        assert_eq!(vec![
+            ident("h1"),
+            Token::Delim('.'),
+            ident("a:b"),
+            Token::Comma,
+            Token::Whitespace,
+            Token::Hash { value: String::from("x"), is_id: true },
+            Token::Whitespace,
+            Token::OpenCurly,
+            Token::Whitespace,
+            ident("margin"),
+            Token::Colon,
+            Token::Dimension(-15.0, String::from("px")),
+            Token::Whitespace,
+            Token::CloseCurly
+        ], tokens);
+    }
+
+    #[test]
+    fn test_tokenize_numbers() {
+        assert_eq!(vec![
+            Token::Number(12.0),
+            Token::Number(0.5),
+            Token::Number(-3.0),
+            Token::Percentage(50.0),
+            Token::Dimension(2.0, String::from("em")),
+            Token::Dimension(1.0, String::from("e")),
+            Token::Number(200.0),
+            Token::Delim('+'),
+            ident("a"),
+            Token::Hash { value: String::from("1a"), is_id: false }
+        ], tokenize("12 +.5 -3 50% 2em 1e 2e2 +a #1a"));
+    }
+
+    #[test]
+    fn test_tokenize_strings_and_urls() {
+        assert_eq!(vec![
+            Token::String(String::from("a\"b")),
+            Token::String(String::from("c\u{E9}d")),
+            Token::String(String::from("ef")),
+            Token::Url(String::from("img/a b.png")),
+            Token::Function(String::from("url")),
+            Token::String(String::from("x.css")),
+            Token::CloseParen,
+            Token::BadUrl,
+            ident("p"),
+            Token::BadString,
+            ident("q")
+        ], tokenize("\"a\\\"b\" 'c\\e9 d' 'e\\\nf' URL( img/a\\ b.png ) url( \"x.css\") url(a b) p \"open\nq"));
+    }
+
+    #[test]
+    fn test_tokenize_other_tokens() {
+        assert_eq!(vec![
+            Token::Cdo,
+            Token::AtKeyword(String::from("media")),
+            Token::Function(String::from("rgb")),
+            Token::CloseParen,
+            Token::UnicodeRange(0x400, 0x4FF),
+            Token::UnicodeRange(0x20, 0x7E),
+            ident("--custom"),
+            ident("-webkit-box"),
+            Token::Delim('@'),
+            Token::Delim('-'),
+            Token::OpenSquare,
+            Token::CloseSquare,
+            Token::Semicolon,
+            Token::Cdc
+        ], tokenize("<!-- @media rgb() U+4?? u+20-7e --custom -webkit-box @ - [ ]; -->"));
+    }
+
+    #[test]
+    fn test_next_token_reports_ranges() {
+        let mut tokenizer = Tokenizer::new(String::from("/* a */ p{}"));
+        assert_eq!((Token::Whitespace, 7..8), tokenizer.next_token());
+        assert_eq!((ident("p"), 8..9), tokenizer.next_token());
+        assert_eq!((Token::OpenCurly, 9..10), tokenizer.next_token());
+        assert_eq!((Token::CloseCurly, 10..11), tokenizer.next_token());
+        assert_eq!((Token::Eof, 11..11), tokenizer.next_token());
+        assert_eq!((Token::Eof, 11..11), tokenizer.next_token());
+    }
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::warn;
+use crate::css_parser::CSSParser;
+use crate::css_parser::stylesheet::Stylesheet;
+use crate::document_loader::document::Document;
+use crate::document_loader::load_error::LoadError;
+use crate::encoding::character_encoding::CharacterEncoding;
+use crate::html_parser::HTMLParser;
+use crate::html_parser::element_data::ElementData;
+use crate::html_parser::dom::Dom;
+use crate::html_parser::node_id::NodeId;
+use crate::html_parser::node_type::NodeType;
+
+pub mod document;
+pub mod load_error;
+
+pub fn load_document(path: &Path) -> Result<Document, LoadError> {
+    let bytes = fs::read(path).map_err(|error| LoadError::Io(path.to_path_buf(), error))?;
+    let mut source_files = vec![path.to_path_buf()];
+    let mut parser = HTMLParser::from_bytes(&bytes);
+    parser.set_file_id(0);
+    let encoding = parser.get_encoding();
+    let dom = parser.parse();
+
+    for error in parser.get_errors() {
+        warn!("{}: {}", path.display(), error);
+    }
+
+    let base_directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let stylesheets = collect_stylesheets(&dom, base_directory, encoding, &mut source_files);
+
+    return Ok(Document {
+        dom,
+        encoding,
+        stylesheets,
+        source_files
+    });
+}
+
+// Collects the stylesheets of every <style> and <link rel="stylesheet"> in document order.
+// A stylesheet that cannot be read is skipped, as a browser would do.
+// Linked stylesheets without their own encoding declaration are decoded with the document's encoding.
+// Each linked stylesheet is added to source_files, and the spans of its rules refer to its index there.
+pub fn collect_stylesheets(dom: &Dom, base_directory: &Path, encoding: CharacterEncoding, source_files: &mut Vec<PathBuf>) -> Vec<Stylesheet> {
+    return dom.descendants(dom.get_document())
+        .filter_map(|node_id| {
+            let element_data = dom.get_element_data(node_id)?;
+
+            return match element_data.tag_name.as_str() {
+                "style" => Some(parse_stylesheet(create_style_element_parser(dom, node_id), Path::new("<style>"))),
+                "link" if is_stylesheet_link(element_data) => load_linked_stylesheet(element_data, base_directory, encoding, source_files),
+                _ => None
+            };
+        })
+        .collect();
+}
+
+fn is_stylesheet_link(element_data: &ElementData) -> bool {
+    return match element_data.get_attribute("rel") {
+        Some(rel) => rel.split_ascii_whitespace().any(|link_type| link_type.eq_ignore_ascii_case("stylesheet")),
+        None => false
+    };
+}
+
+// The spans of a <style> element's rules point into the HTML file, where its text starts.
+fn create_style_element_parser(dom: &Dom, node_id: NodeId) -> CSSParser {
+    let mut parser = CSSParser::new(get_text_content(dom, node_id));
+    let text_span = dom.get_node(node_id).get_first_child().and_then(|child| dom.get_node(child).get_source_span());
+
+    if let Some(text_span) = text_span {
+        parser.set_source_location(text_span.file_id, text_span.start_line, text_span.start_column);
+    }
+
+    return parser;
+}
+
+fn load_linked_stylesheet(element_data: &ElementData, base_directory: &Path, encoding: CharacterEncoding, source_files: &mut Vec<PathBuf>) -> Option<Stylesheet> {
+    let path = resolve_href(element_data.get_attribute("href")?, base_directory)?;
+
+    return match fs::read(&path) {
+        Ok(bytes) => {
+            let mut parser = CSSParser::from_bytes(&bytes, Some(encoding));
+            parser.set_source_location(source_files.len(), 1, 1);
+            source_files.push(path.clone());
+            Some(parse_stylesheet(parser, &path))
+        },
+        Err(error) => {
+            warn!("{}", LoadError::Io(path, error));
+            None
+        }
+    };
+}
+
+// Resolves an href against the directory of the document. Only local files inside that directory
+// can be loaded, so a page cannot read other files of the machine. A root-relative href such as
+// "/css/a.css" is resolved against the directory as well.
+fn resolve_href(href: &str, base_directory: &Path) -> Option<PathBuf> {
+    let href = href.trim();
+    let href = &href[..href.find(|char| char == '?' || char == '#').unwrap_or(href.len())];
+
+    if href.is_empty() || href.contains("://") || href.starts_with("//") {
+        warn!("Skipping stylesheet that is not a local file: {}", href);
+        return None;
+    }
+
+    // Canonical paths have no ".." or symbolic links left, which could lead out of the directory.
+    let base_directory = canonicalize(base_directory)?;
+    let path = canonicalize(&base_directory.join(href.trim_start_matches('/')))?;
+
+    if !path.starts_with(&base_directory) {
+        warn!("Skipping stylesheet outside of the document directory: {}", href);
+        return None;
+    }
+
+    return Some(path);
+}
+
+fn canonicalize(path: &Path) -> Option<PathBuf> {
+    return match path.canonicalize() {
+        Ok(path) => Some(path),
+        Err(error) => {
+            warn!("{}", LoadError::Io(path.to_path_buf(), error));
+            None
+        }
+    };
+}
+
+fn parse_stylesheet(mut parser: CSSParser, path: &Path) -> Stylesheet {
+    let stylesheet = parser.parse();
+
+    for warning in parser.get_warnings() {
+        warn!("{}: {}", path.display(), warning);
+    }
+
+    return stylesheet;
+}
+
+fn get_text_content(dom: &Dom, node_id: NodeId) -> String {
+    return dom.children(node_id).fold(String::new(), |mut acc, child| {
+        if let NodeType::Text(text) = &dom.get_node(child).node_type {
+            acc.push_str(text.as_str());
+        }
+        return acc;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use crate::document_loader::{load_document, resolve_href};
+    use crate::document_loader::load_error::LoadError;
+    use crate::encoding::character_encoding::CharacterEncoding;
+
+    fn create_test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("toy_browser_engine_{}_{}", name, std::process::id()));
+        fs::create_dir_all(directory.join("css")).unwrap();
+        return directory.canonicalize().unwrap();
+    }
+
+    #[test]
+    fn test_load_document() {
+        let directory = create_test_directory("load_document");
+        fs::write(directory.join("css/site.css"), "h1 { margin: auto; }").unwrap();
+        fs::write(directory.join("invalid.css"), "h1 { margin: 2em; }").unwrap();
+        fs::write(directory.join("index.html"), "<html><head>\
+            <style>p { display: none; }</style>\
+            <link rel='icon' href='favicon.ico'>\
+            <link rel='Stylesheet' href='css/site.css?v=2'>\
+            <link rel='stylesheet' href='missing.css'>\
+            <link rel='stylesheet' href='invalid.css'>\
+            <link rel='stylesheet' href='https://example.com/remote.css'>\
+            </head><body><style>div { color: #cc0000; }</style></body></html>").unwrap();
+
+        let document = load_document(&directory.join("index.html")).unwrap();
+        let stylesheets: Vec<String> = document.stylesheets.iter().map(|stylesheet| format!("{}", stylesheet)).collect();
+        assert_eq!(
+            vec![
+                "p { display: none; }",
+                "h1 { margin: auto; }",
+                "h1 { }",
+                "div { color: #rgba(204, 0, 0, 255); }"
+            ],
+            stylesheets
+        );
+
+        // Rules of <style> elements point into the HTML file, and those of linked stylesheets into their own file.
+        assert_eq!(vec![directory.join("index.html"), directory.join("css/site.css"), directory.join("invalid.css")], document.source_files);
+        let rule_spans: Vec<String> = document.stylesheets.iter().map(|stylesheet| format!("{}", stylesheet.rules[0].source_span.unwrap())).collect();
+        assert_eq!(vec!["0:1:20-1:40", "1:1:1-1:21", "2:1:1-1:20", "0:1:296-1:319"], rule_spans);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_load_document_with_legacy_encoding() {
+        let directory = create_test_directory("load_document_with_legacy_encoding");
+        let utf16_stylesheet: Vec<u8> = "\u{FEFF}p { color: red; }".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        fs::write(directory.join("utf16.css"), utf16_stylesheet).unwrap();
+        fs::write(directory.join("index.html"), b"<html><head>\
+            <meta http-equiv='Content-Type' content='text/html; charset=windows-1252'>\
+            <title>\x93Quoted\x94</title>\
+            <link rel='stylesheet' href='utf16.css'>\
+            </head></html>").unwrap();
+
+        let document = load_document(&directory.join("index.html")).unwrap();
+        assert_eq!(CharacterEncoding::Windows1252, document.encoding);
+
+        let title = document.dom.get_elements_by_tag_name(document.dom.get_document(), "title")[0];
+        assert_eq!("<title>\u{201C}Quoted\u{201D}</title>", document.dom.get_outer_html(title));
+        assert_eq!("p { color: red; }", format!("{}", document.stylesheets[0]));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_load_document_reports_missing_file() {
+        let result = load_document(Path::new("/nonexistent/index.html"));
+        assert!(matches!(result, Err(LoadError::Io(_, _))));
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        let directory = create_test_directory("resolve_href");
+        let base_directory = directory.join("css");
+        fs::write(base_directory.join("a.css"), "").unwrap();
+        fs::write(directory.join("b.css"), "").unwrap();
+
+        assert_eq!(Some(base_directory.join("a.css")), resolve_href("a.css#x", &base_directory));
+        assert_eq!(Some(base_directory.join("a.css")), resolve_href(" ../css/./a.css?v=1 ", &base_directory));
+        assert_eq!(None, resolve_href("http://example.com/c.css", &base_directory));
+        assert_eq!(None, resolve_href("", &base_directory));
+        assert_eq!(None, resolve_href("missing.css", &base_directory));
+
+        // Root-relative hrefs start from the document directory, not from the root of the file system.
+        assert_eq!(Some(base_directory.join("a.css")), resolve_href("/a.css", &base_directory));
+        assert_eq!(None, resolve_href("/b.css", &base_directory));
+
+        // Files outside of the document directory cannot be loaded.
+        assert!(directory.join("b.css").exists());
+        assert_eq!(None, resolve_href("../b.css", &base_directory));
+        assert_eq!(None, resolve_href("/../b.css", &base_directory));
+        assert_eq!(None, resolve_href("/../../../../../../../../etc/passwd", &base_directory));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+use crate::css_parser::stylesheet::Stylesheet;
+use crate::encoding::character_encoding::CharacterEncoding;
+use crate::html_parser::dom::Dom;
+
+pub struct Document {
+    pub dom: Dom,
+    // The encoding the document was decoded with, which linked stylesheets fall back to.
+    pub encoding: CharacterEncoding,
+    // Author stylesheets in document order, as expected by the style stage.
+    pub stylesheets: Vec<Stylesheet>,
+    // The files the document was loaded from, indexed by the file_id of source spans.
+    // The HTML file comes first.
+    pub source_files: Vec<PathBuf>
+}
@@ -0,0 +1,18 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error)
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(path, error) => write!(f, "cannot read {}: {}", path.display(), error)
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
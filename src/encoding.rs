@@ -0,0 +1,303 @@
+use crate::encoding::character_encoding::CharacterEncoding;
+
+pub mod character_encoding;
+
+// The bytes 0x80..=0x9F of Windows-1252, which differ from ISO-8859-1. The five bytes that are
+// not listed are undefined and decode to the C1 control with the same code point.
+pub(crate) const WINDOWS_1252_REPLACEMENTS: [(u32, char); 27] = [
+    (0x80, '\u{20AC}'), (0x82, '\u{201A}'), (0x83, '\u{0192}'), (0x84, '\u{201E}'),
+    (0x85, '\u{2026}'), (0x86, '\u{2020}'), (0x87, '\u{2021}'), (0x88, '\u{02C6}'),
+    (0x89, '\u{2030}'), (0x8A, '\u{0160}'), (0x8B, '\u{2039}'), (0x8C, '\u{0152}'),
+    (0x8E, '\u{017D}'), (0x91, '\u{2018}'), (0x92, '\u{2019}'), (0x93, '\u{201C}'),
+    (0x94, '\u{201D}'), (0x95, '\u{2022}'), (0x96, '\u{2013}'), (0x97, '\u{2014}'),
+    (0x98, '\u{02DC}'), (0x99, '\u{2122}'), (0x9A, '\u{0161}'), (0x9B, '\u{203A}'),
+    (0x9C, '\u{0153}'), (0x9E, '\u{017E}'), (0x9F, '\u{0178}')
+];
+
+// How far into a document a <meta charset> is looked for.
+const PRESCAN_LENGTH: usize = 1024;
+
+// Decodes an HTML document, choosing the encoding from its byte order mark, then from a
+// <meta> declaration, then by checking whether the bytes are valid UTF-8.
+pub fn decode_html(bytes: &[u8]) -> (String, CharacterEncoding) {
+    if let Some((encoding, bom_length)) = sniff_bom(bytes) {
+        return (decode(&bytes[bom_length..], encoding), encoding);
+    }
+
+    let encoding = match prescan_meta_charset(bytes) {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => CharacterEncoding::Utf8,
+        None => CharacterEncoding::Windows1252
+    };
+
+    return (decode(bytes, encoding), encoding);
+}
+
+// Decodes a stylesheet, choosing the encoding from its byte order mark, then from an @charset
+// rule, then from the document that refers to it, and otherwise using UTF-8.
+pub fn decode_css(bytes: &[u8], environment_encoding: Option<CharacterEncoding>) -> String {
+    if let Some((encoding, bom_length)) = sniff_bom(bytes) {
+        return decode(&bytes[bom_length..], encoding);
+    }
+
+    let encoding = get_css_charset(bytes)
+        .or(environment_encoding)
+        .unwrap_or(CharacterEncoding::Utf8);
+
+    return decode(bytes, encoding);
+}
+
+// Returns the encoding given by a byte order mark, and the length of the mark.
+pub fn sniff_bom(bytes: &[u8]) -> Option<(CharacterEncoding, usize)> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some((CharacterEncoding::Utf8, 3));
+    }
+
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return Some((CharacterEncoding::Utf16Be, 2));
+    }
+
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return Some((CharacterEncoding::Utf16Le, 2));
+    }
+
+    return None;
+}
+
+// Invalid byte sequences are replaced with U+FFFD.
+pub fn decode(bytes: &[u8], encoding: CharacterEncoding) -> String {
+    return match encoding {
+        CharacterEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        CharacterEncoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+        CharacterEncoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+        CharacterEncoding::Iso88591 => bytes.iter().map(|byte| char::from(*byte)).collect(),
+        CharacterEncoding::Windows1252 => bytes.iter().map(|byte| decode_windows_1252_byte(*byte)).collect()
+    };
+}
+
+pub(crate) fn decode_windows_1252_byte(byte: u8) -> char {
+    return WINDOWS_1252_REPLACEMENTS
+        .iter()
+        .find(|(code_point, _)| *code_point == byte as u32)
+        .map_or(char::from(byte), |(_, char)| *char);
+}
+
+fn decode_utf16<F>(bytes: &[u8], to_code_unit: F) -> String
+    where F: Fn([u8; 2]) -> u16
+{
+    let code_units = bytes.chunks_exact(2).map(|pair| to_code_unit([pair[0], pair[1]]));
+    let mut result: String = char::decode_utf16(code_units)
+        .map(|char| char.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+
+    // A trailing odd byte is half a code unit.
+    if bytes.len() % 2 == 1 {
+        result.push(char::REPLACEMENT_CHARACTER);
+    }
+
+    return result;
+}
+
+// Looks for <meta charset="..."> or <meta http-equiv="Content-Type" content="...; charset=...">
+// near the start of a document, skipping comments and other tags on the way.
+pub fn prescan_meta_charset(bytes: &[u8]) -> Option<CharacterEncoding> {
+    let bytes = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let rest = &bytes[position..];
+
+        if rest.starts_with(b"<!--") {
+            position += find_bytes(&rest[4..], b"-->").map_or(rest.len(), |index| index + 4 + 3);
+        } else if starts_with_ignore_case(rest, b"<meta") && rest.get(5).is_some_and(|byte| byte.is_ascii_whitespace() || *byte == b'/') {
+            position += 5;
+
+            if let Some(encoding) = get_meta_charset(bytes, &mut position) {
+                // A page that says it is UTF-16 cannot be, since this declaration was readable as ASCII.
+                return Some(if encoding.is_utf16() { CharacterEncoding::Utf8 } else { encoding });
+            }
+        } else if rest.len() > 1 && rest[0] == b'<' && (rest[1].is_ascii_alphabetic() || rest[1] == b'/') {
+            // Other tags are skipped together with their attributes, whose values may contain '>'.
+            position += rest[1..].iter().position(|byte| byte.is_ascii_whitespace() || *byte == b'>').map_or(rest.len(), |index| index + 1);
+            while get_attribute(bytes, &mut position).is_some() {}
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"<?") {
+            position += rest.iter().position(|byte| *byte == b'>').map_or(rest.len(), |index| index + 1);
+        } else {
+            position += 1;
+        }
+    }
+
+    return None;
+}
+
+// Reads the attributes of a <meta> tag and returns the encoding it declares, if any.
+fn get_meta_charset(bytes: &[u8], position: &mut usize) -> Option<CharacterEncoding> {
+    let mut is_content_type = false;
+    let mut charset = None;
+    let mut charset_from_content = None;
+
+    while let Some((name, value)) = get_attribute(bytes, position) {
+        match name.as_str() {
+            "http-equiv" => is_content_type = value == "content-type",
+            "charset" if charset.is_none() => charset = Some(value),
+            "content" if charset_from_content.is_none() => charset_from_content = extract_charset_from_content(value.as_str()),
+            _ => {}
+        }
+    }
+
+    // A charset in the content attribute only counts together with http-equiv="Content-Type".
+    return match (charset, charset_from_content) {
+        (Some(charset), _) => CharacterEncoding::from_label(charset.as_str()),
+        (None, Some(charset)) if is_content_type => CharacterEncoding::from_label(charset.as_str()),
+        _ => None
+    };
+}
+
+// Reads one attribute of a tag, lowercased, or returns None at the end of the tag.
+fn get_attribute(bytes: &[u8], position: &mut usize) -> Option<(String, String)> {
+    let skip_while = |position: &mut usize, predicate: &dyn Fn(u8) -> bool| {
+        while *position < bytes.len() && predicate(bytes[*position]) {
+            *position += 1;
+        }
+    };
+
+    skip_while(position, &|byte| byte.is_ascii_whitespace() || byte == b'/');
+
+    if *position >= bytes.len() || bytes[*position] == b'>' {
+        *position += 1;
+        return None;
+    }
+
+    let name_start = *position;
+    *position += 1;
+    skip_while(position, &|byte| !byte.is_ascii_whitespace() && !matches!(byte, b'=' | b'/' | b'>'));
+    let name = String::from_utf8_lossy(&bytes[name_start..*position]).to_ascii_lowercase();
+
+    skip_while(position, &|byte| byte.is_ascii_whitespace());
+
+    if *position >= bytes.len() || bytes[*position] != b'=' {
+        return Some((name, String::new()));
+    }
+
+    *position += 1;
+    skip_while(position, &|byte| byte.is_ascii_whitespace());
+
+    let value = match bytes.get(*position) {
+        Some(quote @ (b'"' | b'\'')) => {
+            let quote = *quote;
+            *position += 1;
+            let value_start = *position;
+            skip_while(position, &|byte| byte != quote);
+            let value = &bytes[value_start..*position];
+            *position += 1;
+            value
+        },
+        _ => {
+            let value_start = *position;
+            skip_while(position, &|byte| !byte.is_ascii_whitespace() && byte != b'>');
+            &bytes[value_start..*position]
+        }
+    };
+
+    return Some((name, String::from_utf8_lossy(value).to_ascii_lowercase()));
+}
+
+// Extracts the encoding label from a content attribute such as "text/html; charset=utf-8".
+fn extract_charset_from_content(content: &str) -> Option<String> {
+    let mut rest = content;
+
+    loop {
+        let index = rest.find("charset")?;
+        rest = rest[index + "charset".len()..].trim_start_matches(|char: char| char.is_ascii_whitespace());
+
+        if let Some(value) = rest.strip_prefix('=') {
+            rest = value.trim_start_matches(|char: char| char.is_ascii_whitespace());
+            break;
+        }
+    }
+
+    let label = match rest.chars().next()? {
+        quote @ ('"' | '\'') => &rest[1..rest[1..].find(quote)? + 1],
+        _ => &rest[..rest.find(|char: char| char.is_ascii_whitespace() || char == ';').unwrap_or(rest.len())]
+    };
+
+    return Some(String::from(label));
+}
+
+// Reads the label of an @charset rule, which must be the very first thing in a stylesheet.
+fn get_css_charset(bytes: &[u8]) -> Option<CharacterEncoding> {
+    let bytes = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+    let rest = bytes.strip_prefix(b"@charset \"")?;
+    let end = find_bytes(rest, b"\";")?;
+    let encoding = CharacterEncoding::from_label(std::str::from_utf8(&rest[..end]).ok()?)?;
+
+    // As in HTML, a UTF-16 label in ASCII-compatible bytes is wrong.
+    return Some(if encoding.is_utf16() { CharacterEncoding::Utf8 } else { encoding });
+}
+
+fn find_bytes(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    return bytes.windows(pattern.len()).position(|window| window == pattern);
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    return bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{decode, decode_css, decode_html, prescan_meta_charset, sniff_bom};
+    use crate::encoding::character_encoding::CharacterEncoding;
+
+    #[test]
+    fn test_sniff_bom() {
+        assert_eq!(Some((CharacterEncoding::Utf8, 3)), sniff_bom(b"\xEF\xBB\xBFa"));
+        assert_eq!(Some((CharacterEncoding::Utf16Be, 2)), sniff_bom(b"\xFE\xFF\x00a"));
+        assert_eq!(Some((CharacterEncoding::Utf16Le, 2)), sniff_bom(b"\xFF\xFEa\x00"));
+        assert_eq!(None, sniff_bom(b"<html>"));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!("caf\u{E9} \u{FFFD}!", decode(b"caf\xC3\xA9 \xC3!", CharacterEncoding::Utf8));
+        assert_eq!("a\u{1F4A3}\u{FFFD}", decode(b"a\x00\x3D\xD8\xA3\xDC\x00", CharacterEncoding::Utf16Le));
+        assert_eq!("a\u{FFFD}b", decode(b"\x00a\xDC\x00\x00b", CharacterEncoding::Utf16Be));
+        assert_eq!("\u{E9}\u{80}", decode(b"\xE9\x80", CharacterEncoding::Iso88591));
+        assert_eq!("\u{E9}\u{20AC}\u{81}\u{201C}", decode(b"\xE9\x80\x81\x93", CharacterEncoding::Windows1252));
+    }
+
+    #[test]
+    fn test_prescan_meta_charset() {
+        assert_eq!(Some(CharacterEncoding::Windows1252), prescan_meta_charset(b"<html><head><META CharSet='latin1'>"));
+        assert_eq!(
+            Some(CharacterEncoding::Utf8),
+            prescan_meta_charset(b"<meta content=\"text/html; charset='utf-8'\" http-equiv=\"Content-Type\">")
+        );
+        assert_eq!(Some(CharacterEncoding::Utf8), prescan_meta_charset(b"<meta charset=utf-16>"));
+
+        // Declarations inside comments and attribute values, or without http-equiv, do not count.
+        assert_eq!(None, prescan_meta_charset(b"<!-- <meta charset=latin1> --><p title='<meta charset=latin1>'>"));
+        assert_eq!(None, prescan_meta_charset(b"<meta content='text/html; charset=latin1'><meta charset=unknown>"));
+
+        let late_declaration = [vec![b' '; 1024], b"<meta charset=latin1>".to_vec()].concat();
+        assert_eq!(None, prescan_meta_charset(&late_declaration));
+    }
+
+    #[test]
+    fn test_decode_html() {
+        assert_eq!((String::from("<p>\u{E9}"), CharacterEncoding::Utf16Le), decode_html(b"\xFF\xFE<\x00p\x00>\x00\xE9\x00"));
+        assert_eq!(
+            (String::from("<meta charset=windows-1252><p>\u{2019}"), CharacterEncoding::Windows1252),
+            decode_html(b"<meta charset=windows-1252><p>\x92")
+        );
+        assert_eq!((String::from("<p>\u{E9}"), CharacterEncoding::Utf8), decode_html(b"<p>\xC3\xA9"));
+        assert_eq!((String::from("<p>\u{E9}"), CharacterEncoding::Windows1252), decode_html(b"<p>\xE9"));
+    }
+
+    #[test]
+    fn test_decode_css() {
+        assert_eq!("@charset \"latin1\"; p::after { content: \"\u{E9}\"; }", decode_css(b"@charset \"latin1\"; p::after { content: \"\xE9\"; }", None));
+        assert_eq!("p { content: \"\u{E9}\"; }", decode_css(b"p { content: \"\xE9\"; }", Some(CharacterEncoding::Windows1252)));
+        assert_eq!("p { content: \"\u{FFFD}\"; }", decode_css(b"p { content: \"\xE9\"; }", None));
+        assert_eq!("p {}", decode_css(b"\xEF\xBB\xBFp {}", Some(CharacterEncoding::Windows1252)));
+    }
+}
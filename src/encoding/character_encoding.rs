@@ -0,0 +1,45 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CharacterEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Iso88591,
+    Windows1252
+}
+
+impl CharacterEncoding {
+    // Looks up an encoding by one of the labels of the WHATWG Encoding standard, as used in
+    // <meta charset> and @charset. Like browsers, it reads pages labeled ISO-8859-1 or ASCII
+    // as Windows-1252, which they almost always are.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let encoding = match label.trim_matches(|char: char| char.is_ascii_whitespace()).to_ascii_lowercase().as_str() {
+            "unicode-1-1-utf-8" | "unicode11utf8" | "unicode20utf8" | "utf-8" | "utf8" | "x-unicode20utf8" => CharacterEncoding::Utf8,
+            "csunicode" | "iso-10646-ucs-2" | "ucs-2" | "unicode" | "unicodefeff" | "utf-16" | "utf-16le" => CharacterEncoding::Utf16Le,
+            "unicodefffe" | "utf-16be" => CharacterEncoding::Utf16Be,
+            "ansi_x3.4-1968" | "ascii" | "cp1252" | "cp819" | "csisolatin1" | "ibm819" | "iso-8859-1" | "iso-ir-100"
+            | "iso8859-1" | "iso88591" | "iso_8859-1" | "iso_8859-1:1987" | "l1" | "latin1" | "us-ascii"
+            | "windows-1252" | "x-cp1252" => CharacterEncoding::Windows1252,
+            _ => return None
+        };
+
+        return Some(encoding);
+    }
+
+    pub fn is_utf16(&self) -> bool {
+        return matches!(self, CharacterEncoding::Utf16Le | CharacterEncoding::Utf16Be);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::character_encoding::CharacterEncoding;
+
+    #[test]
+    fn test_from_label() {
+        assert_eq!(Some(CharacterEncoding::Utf8), CharacterEncoding::from_label(" UTF8\n"));
+        assert_eq!(Some(CharacterEncoding::Utf16Le), CharacterEncoding::from_label("utf-16"));
+        assert_eq!(Some(CharacterEncoding::Utf16Be), CharacterEncoding::from_label("UTF-16BE"));
+        assert_eq!(Some(CharacterEncoding::Windows1252), CharacterEncoding::from_label("ISO-8859-1"));
+        assert_eq!(None, CharacterEncoding::from_label("utf-9"));
+    }
+}
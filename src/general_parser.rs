@@ -1,70 +1,289 @@
+use std::ops::Range;
+use crate::general_parser::checkpoint::Checkpoint;
+use crate::parse_error::{ParseError, ParseErrorKind};
+use crate::source_span::SourceSpan;
+
+pub mod checkpoint;
+
+// Reads input for the HTML and CSS parsers. Positions are byte offsets, and scanning works on
+// bytes where the input is ASCII. Consumed input is returned as slices of the content, so the
+// parsers only allocate for what they keep.
 pub struct GeneralParser {
-    pub content: String,
-    pub current_position: usize
+    content: String,
+    current_position: usize,
+    // The 1-based line and column of current_position, updated as characters are consumed.
+    line: usize,
+    column: usize
 }
 
 impl GeneralParser {
+    pub fn new(content: String) -> Self {
+        Self {
+            content,
+            current_position: 0,
+            line: 1,
+            column: 1
+        }
+    }
+
+    pub fn get_position(&self) -> usize {
+        return self.current_position;
+    }
+
+    pub fn get_current_line_and_column(&self) -> (usize, usize) {
+        return (self.line, self.column);
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        return Checkpoint {
+            position: self.current_position,
+            line: self.line,
+            column: self.column
+        };
+    }
+
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.current_position = checkpoint.position;
+        self.line = checkpoint.line;
+        self.column = checkpoint.column;
+    }
+
     pub fn peek_current_char(&self) -> char {
-        return self.content[self.current_position..].chars().next().unwrap()
+        return self.try_peek_current_byte_char().unwrap();
+    }
+
+    fn try_peek_current_byte_char(&self) -> Option<char> {
+        let byte = *self.content.as_bytes().get(self.current_position)?;
+
+        if byte.is_ascii() {
+            return Some(byte as char);
+        }
+
+        return self.content[self.current_position..].chars().next();
+    }
+
+    pub fn try_peek_current_char(&self) -> Result<char, ParseError> {
+        if self.is_eof() {
+            return Err(self.create_error(ParseErrorKind::UnexpectedEof, self.current_position));
+        }
+
+        return Ok(self.peek_current_char());
+    }
+
+    // Returns the character n characters ahead of the current one, so peek_nth(0) is the current character.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        return self.content[self.current_position..].chars().nth(n);
     }
 
     pub fn is_started_with(&self, starting_string: &str) -> bool {
-        return self.content[self.current_position..].starts_with(starting_string)
+        return self.content.as_bytes()[self.current_position..].starts_with(starting_string.as_bytes())
+    }
+
+    pub fn is_started_with_ignore_case(&self, starting_string: &str) -> bool {
+        return self.content.as_bytes()[self.current_position..]
+            .get(..starting_string.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(starting_string.as_bytes()))
     }
 
     pub fn is_eof(&self) -> bool {
-        return self.content[self.current_position..].chars().next().is_none()
+        return self.current_position >= self.content.len()
     }
 
     pub fn pop_current_char(&mut self) -> char {
         let current_char = self.peek_current_char();
         self.current_position += current_char.len_utf8();
+
+        if current_char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         return current_char;
     }
 
-    pub fn consume_while<F>(&mut self, checker_function: F) -> String
+    // Moves to a later position, updating the line and column for the skipped content.
+    fn advance_to(&mut self, position: usize) {
+        let skipped_content = &self.content.as_bytes()[self.current_position..position];
+
+        match skipped_content.iter().rposition(|byte| *byte == b'\n') {
+            Some(last_line_feed) => {
+                self.line += skipped_content.iter().filter(|byte| **byte == b'\n').count();
+                self.column = count_chars(&skipped_content[last_line_feed + 1..]) + 1;
+            },
+            None => self.column += count_chars(skipped_content)
+        }
+
+        self.current_position = position;
+    }
+
+    // Consumes the string if the input continues with it, and reports whether it did.
+    pub fn consume_str(&mut self, expected: &str) -> bool {
+        if !self.is_started_with(expected) {
+            return false;
+        }
+
+        self.advance_to(self.current_position + expected.len());
+        return true;
+    }
+
+    pub fn consume_str_ignore_case(&mut self, expected: &str) -> bool {
+        if !self.is_started_with_ignore_case(expected) {
+            return false;
+        }
+
+        self.advance_to(self.current_position + expected.len());
+        return true;
+    }
+
+    pub fn expect_str(&mut self, expected: &str) -> Result<(), ParseError> {
+        let start_position = self.current_position;
+
+        if !self.consume_str(expected) {
+            return Err(self.create_error(ParseErrorKind::ExpectedString(String::from(expected)), start_position));
+        }
+
+        return Ok(());
+    }
+
+    pub fn consume_while<F>(&mut self, checker_function: F) -> &str
         where F: Fn(char) -> bool
     {
-        let mut result = String::new();
+        let start_position = self.current_position;
+        let bytes = self.content.as_bytes();
+        let mut position = start_position;
+
+        while position < bytes.len() {
+            let char = match bytes[position] {
+                byte if byte.is_ascii() => byte as char,
+                _ => self.content[position..].chars().next().unwrap()
+            };
 
-        while !self.is_eof() && checker_function(self.peek_current_char()) {
-            result.push(self.pop_current_char());
+            if !checker_function(char) {
+                break;
+            }
+
+            position += char.len_utf8();
         }
 
-        return result;
+        self.advance_to(position);
+        return &self.content[start_position..position];
     }
 
     pub fn consume_whitespaces(&mut self) {
         self.consume_while(|char| char.is_whitespace());
     }
+
+    pub fn expect_char(&mut self, expected: char) -> Result<(), ParseError> {
+        let start_position = self.current_position;
+
+        if self.is_eof() {
+            return Err(self.create_error(ParseErrorKind::UnexpectedEof, start_position));
+        }
+
+        let found = self.pop_current_char();
+
+        if found != expected {
+            return Err(self.create_error(ParseErrorKind::ExpectedCharacter { expected, found }, start_position));
+        }
+
+        return Ok(());
+    }
+
+    // Returns the 1-based line and column of a byte position; columns count characters.
+    pub fn get_line_and_column(&self, position: usize) -> (usize, usize) {
+        let bytes = self.content.as_bytes();
+
+        if position > self.current_position {
+            let preceding_content = &bytes[..position];
+            let line = preceding_content.iter().filter(|byte| **byte == b'\n').count() + 1;
+            let line_start = preceding_content.iter().rposition(|byte| *byte == b'\n').map_or(0, |index| index + 1);
+            return (line, count_chars(&preceding_content[line_start..]) + 1);
+        }
+
+        // Earlier positions are counted back from the current line and column,
+        // so that locating a recent position does not rescan the whole input.
+        let skipped_content = &bytes[position..self.current_position];
+        let skipped_lines = skipped_content.iter().filter(|byte| **byte == b'\n').count();
+
+        if skipped_lines == 0 {
+            return (self.line, self.column - count_chars(skipped_content));
+        }
+
+        let line_start = bytes[..position].iter().rposition(|byte| *byte == b'\n').map_or(0, |index| index + 1);
+        return (self.line - skipped_lines, count_chars(&bytes[line_start..position]) + 1);
+    }
+
+    pub fn get_source_span(&self, file_id: usize, range: Range<usize>) -> SourceSpan {
+        let (start_line, start_column) = self.get_line_and_column(range.start);
+        let (end_line, end_column) = self.get_line_and_column(range.end);
+
+        return SourceSpan {
+            file_id,
+            start_line,
+            start_column,
+            end_line,
+            end_column
+        };
+    }
+
+    // Creates an error spanning from start_position to the current position.
+    pub fn create_error(&self, kind: ParseErrorKind, start_position: usize) -> ParseError {
+        let (line, column) = self.get_line_and_column(start_position);
+
+        return ParseError {
+            kind,
+            line,
+            column,
+            span: start_position..self.current_position
+        };
+    }
+
+    pub fn consume_until(&mut self, ending_string: &str) -> &str {
+        let start_position = self.current_position;
+        let end_position = self.content[start_position..]
+            .find(ending_string)
+            .map_or(self.content.len(), |index| start_position + index);
+
+        self.advance_to(end_position);
+        return &self.content[start_position..end_position];
+    }
+}
+
+// Counts the characters in valid UTF-8 by skipping continuation bytes.
+fn count_chars(bytes: &[u8]) -> usize {
+    return bytes.iter().filter(|byte| (**byte & 0xC0) != 0x80).count();
 }
 
 #[cfg(test)]
 mod tests {
     use crate::general_parser::GeneralParser;
+    use crate::parse_error::ParseErrorKind;
+
+    fn create_parser_at(content: &str, position: usize) -> GeneralParser {
+        let mut parser = GeneralParser::new(String::from(content));
+
+        while parser.get_position() < position {
+            parser.pop_current_char();
+        }
+
+        return parser;
+    }
 
     #[test]
     fn test_is_eof() {
-        let parser_eof = GeneralParser {
-            content: String::from("asdfghjkl"),
-            current_position: 9
-        };
+        let parser_eof = create_parser_at("asdfghjkl", 9);
 
-        let parser_not_eof = GeneralParser {
-            content: String::from("asdfghjkl"),
-            current_position: 8
-        };
+        let parser_not_eof = create_parser_at("asdfghjkl", 8);
 
-        assert_eq!(true, parser_eof.is_eof());
-        assert_eq!(false, parser_not_eof.is_eof());
+        assert!(parser_eof.is_eof());
+        assert!(!parser_not_eof.is_eof());
     }
 
     #[test]
     fn test_consume_char() {
-        let mut parser = GeneralParser {
-            content: String::from("dAgßjℝ💣"),
-            current_position: 1
-        };
+        let mut parser = create_parser_at("dAgßjℝ💣", 1);
 
         assert_eq!('A', parser.pop_current_char());
         assert_eq!('g', parser.pop_current_char());
@@ -72,16 +291,155 @@ mod tests {
         assert_eq!('j', parser.pop_current_char());
         assert_eq!('ℝ', parser.pop_current_char());
         assert_eq!('💣', parser.pop_current_char());
-        assert_eq!(true, parser.is_eof());
+        assert!(parser.is_eof());
     }
 
     #[test]
     fn test_consume_whitespaces() {
-        let mut parser = GeneralParser {
-            content: String::from("d    A"),
-            current_position: 1
-        };
+        let mut parser = create_parser_at("d    A", 1);
         parser.consume_whitespaces();
         assert_eq!('A', parser.peek_current_char());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_is_started_with_ignore_case() {
+        let parser = GeneralParser::new(String::from("<!doctype html>"));
+
+        assert!(parser.is_started_with_ignore_case("<!DOCTYPE"));
+        assert!(!parser.is_started_with_ignore_case("<!DOCTYPEs html>!"));
+    }
+
+    #[test]
+    fn test_consume_until() {
+        let mut parser = create_parser_at("<!-- a - b -->c", 4);
+
+        assert_eq!(" a - b ", parser.consume_until("-->"));
+        assert!(parser.is_started_with("-->"));
+    }
+
+    #[test]
+    fn test_consume_while_across_lines_and_multi_byte_characters() {
+        let mut parser = GeneralParser::new(String::from("ℝé\n💣ab\n\ncd"));
+
+        assert_eq!("ℝé\n💣", parser.consume_while(|char| !char.is_ascii_alphabetic()));
+        assert_eq!(10, parser.get_position());
+        assert_eq!((2, 2), parser.get_current_line_and_column());
+
+        assert_eq!("", parser.consume_while(|char| char == 'x'));
+        assert_eq!((2, 2), parser.get_current_line_and_column());
+
+        assert_eq!("ab\n\ncd", parser.consume_while(|_| true));
+        assert_eq!(16, parser.get_position());
+        assert_eq!((4, 3), parser.get_current_line_and_column());
+        assert!(parser.is_eof());
+
+        // Scanning stops right before a multi-byte character, without splitting it.
+        let mut parser = GeneralParser::new(String::from("aé"));
+        assert_eq!("a", parser.consume_while(|char| char.is_ascii()));
+        assert_eq!('é', parser.peek_current_char());
+        assert_eq!((1, 2), parser.get_current_line_and_column());
+    }
+
+    #[test]
+    fn test_consume_until_across_lines_and_multi_byte_characters() {
+        let mut parser = GeneralParser::new(String::from("/* ℝ\n💣 */x💣y"));
+        parser.consume_str("/*");
+
+        assert_eq!(" ℝ\n💣 ", parser.consume_until("*/"));
+        assert_eq!(12, parser.get_position());
+        assert_eq!((2, 3), parser.get_current_line_and_column());
+
+        parser.consume_str("*/");
+        assert_eq!("x", parser.consume_until("💣"));
+        assert_eq!((2, 6), parser.get_current_line_and_column());
+
+        // Without the ending string, the rest of the input is consumed.
+        assert_eq!("💣y", parser.consume_until("*/"));
+        assert_eq!(20, parser.get_position());
+        assert_eq!((2, 8), parser.get_current_line_and_column());
+        assert_eq!((2, 7), parser.get_line_and_column(19));
+    }
+
+    #[test]
+    fn test_expect_char() {
+        let mut parser = create_parser_at("a:b", 1);
+
+        assert!(parser.expect_char(':').is_ok());
+
+        let error = parser.expect_char(';').unwrap_err();
+        assert_eq!(ParseErrorKind::ExpectedCharacter { expected: ';', found: 'b' }, error.kind);
+        assert_eq!(2..3, error.span);
+
+        let error = parser.expect_char(';').unwrap_err();
+        assert_eq!(ParseErrorKind::UnexpectedEof, error.kind);
+        assert_eq!(3..3, error.span);
+    }
+
+    #[test]
+    fn test_get_line_and_column() {
+        let parser = GeneralParser::new(String::from("ab\nℝ💣d\n\nx"));
+
+        assert_eq!((1, 1), parser.get_line_and_column(0));
+        assert_eq!((1, 3), parser.get_line_and_column(2));
+        assert_eq!((2, 3), parser.get_line_and_column(10));
+        assert_eq!((4, 1), parser.get_line_and_column(13));
+
+        // Positions before the current one are counted back from the tracked line and column.
+        let parser = create_parser_at("ab\nℝ💣d\n\nx", 14);
+        assert_eq!((4, 2), parser.get_current_line_and_column());
+        assert_eq!((1, 3), parser.get_line_and_column(2));
+        assert_eq!((2, 3), parser.get_line_and_column(10));
+        assert_eq!((3, 1), parser.get_line_and_column(12));
+        assert_eq!((4, 1), parser.get_line_and_column(13));
+    }
+
+    #[test]
+    fn test_checkpoint_and_rewind() {
+        let mut parser = GeneralParser::new(String::from("a:hover {\n}"));
+        let checkpoint = parser.checkpoint();
+
+        parser.consume_while(|char| char != '{');
+        parser.pop_current_char();
+        parser.consume_whitespaces();
+        assert_eq!((2, 1), parser.get_current_line_and_column());
+
+        parser.rewind(checkpoint);
+        assert_eq!(0, parser.get_position());
+        assert_eq!((1, 1), parser.get_current_line_and_column());
+        assert_eq!('a', parser.peek_current_char());
+    }
+
+    #[test]
+    fn test_peek_nth() {
+        let parser = create_parser_at("<!ℝ-", 1);
+        assert_eq!(Some('!'), parser.peek_nth(0));
+        assert_eq!(Some('ℝ'), parser.peek_nth(1));
+        assert_eq!(Some('-'), parser.peek_nth(2));
+        assert_eq!(None, parser.peek_nth(3));
+    }
+
+    #[test]
+    fn test_consume_and_expect_str() {
+        let mut parser = GeneralParser::new(String::from("<!DOCTYPE html>\n<!--"));
+        assert!(!parser.consume_str("<!doctype"));
+        assert!(parser.consume_str_ignore_case("<!doctype"));
+        assert_eq!(' ', parser.peek_current_char());
+
+        parser.consume_while(|char| char != '<');
+        assert!(parser.expect_str("<!--").is_ok());
+        assert_eq!((2, 5), parser.get_current_line_and_column());
+
+        let mut parser = GeneralParser::new(String::from("/* x"));
+        let error = parser.expect_str("/*/").unwrap_err();
+        assert_eq!(ParseErrorKind::ExpectedString(String::from("/*/")), error.kind);
+        assert_eq!(0..0, error.span);
+        assert_eq!(0, parser.get_position());
+    }
+
+    #[test]
+    fn test_get_source_span() {
+        let parser = GeneralParser::new(String::from("<p>\n  ℝ</p>"));
+
+        assert_eq!("3:1:1-2:8", format!("{}", parser.get_source_span(3, 0..13)));
+    }
+}
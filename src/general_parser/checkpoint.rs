@@ -0,0 +1,7 @@
+// A saved position of a GeneralParser, to rewind to when a lookahead does not match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkpoint {
+    pub(crate) position: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize
+}
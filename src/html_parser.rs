@@ -1,117 +1,378 @@
-use std::collections::HashMap;
-use crate::general_parser::GeneralParser;
-use crate::html_parser::node::Node;
+use crate::encoding::character_encoding::CharacterEncoding;
+use crate::encoding::decode_html;
+use crate::html_parser::dom::Dom;
+use crate::html_parser::tokenizer::Tokenizer;
+use crate::html_parser::tree_builder::TreeBuilder;
+use crate::parse_error::ParseError;
 
+pub mod dom;
+pub mod dom_error;
+pub mod mutation;
+pub mod mutation_record;
 pub mod node;
+pub mod node_id;
+pub mod node_iterator;
 pub mod node_type;
 pub mod element_data;
+pub mod doctype_data;
+pub mod character_reference;
+pub mod token;
+pub mod tokenizer;
+pub mod tree_builder;
+pub mod serializer;
+pub mod query;
+pub mod quirks_mode;
+mod named_character_references;
+
+// Elements that can never have children and are written without an end tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr"
+];
+
+pub fn is_void_element(tag_name: &str) -> bool {
+    return VOID_ELEMENTS.contains(&tag_name);
+}
+
+// Elements whose content is text up to their end tag; markup inside is neither parsed nor escaped.
+pub(crate) const RAW_TEXT_ELEMENTS: [&str; 7] = ["iframe", "noembed", "noframes", "plaintext", "script", "style", "xmp"];
 
 pub struct HTMLParser {
-    general_parser: GeneralParser
+    tokenizer: Tokenizer,
+    encoding: CharacterEncoding
 }
 
 impl HTMLParser {
-    pub fn parse_tag_name(&mut self) -> String {
-        return self.general_parser.consume_while(|char| {
-            match char {
-                'a' ..= 'z' | 'A' ..= 'Z' | '0' ..= '9' => true,
-                _ => false
-            }
-        });
+    pub fn new(content: String) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(content),
+            encoding: CharacterEncoding::Utf8
+        }
     }
 
-    pub fn parse_nodes(&mut self) -> Vec<Node> {
-        let mut nodes = Vec::new();
+    // Decodes the document with the encoding given by its byte order mark or <meta charset>.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let (content, encoding) = decode_html(bytes);
 
-        while !self.general_parser.is_eof() && !self.general_parser.is_started_with("</") {
-            self.general_parser.consume_whitespaces();
-            nodes.push(self.parse_node());
+        Self {
+            tokenizer: Tokenizer::new(content),
+            encoding
         }
+    }
+
+    pub fn get_encoding(&self) -> CharacterEncoding {
+        return self.encoding;
+    }
+
+    // Sets the file that the source spans of the parsed nodes refer to.
+    pub fn set_file_id(&mut self, file_id: usize) {
+        self.tokenizer.set_file_id(file_id);
+    }
+
+    // Always returns a Dom; errors in the markup are recovered from and can be read with get_errors().
+    pub fn parse(&mut self) -> Dom {
+        let mut tree_builder = TreeBuilder::new();
+
+        while let Some((token, source_span)) = self.tokenizer.next_token() {
+            tree_builder.process_token(token, Some(source_span));
+        }
+
+        return tree_builder.finish();
+    }
+
+    pub fn get_errors(&self) -> &[ParseError] {
+        return self.tokenizer.get_errors();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::character_encoding::CharacterEncoding;
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::node_id::NodeId;
+    use crate::html_parser::node_type::NodeType;
+    use crate::html_parser::quirks_mode::QuirksMode;
+    use crate::parse_error::ParseErrorKind;
 
-        return nodes;
+    fn get_children(dom: &Dom, node_id: NodeId) -> Vec<NodeId> {
+        return dom.children(node_id).collect();
     }
 
-    pub fn parse_node(&mut self) -> Node {
-        if self.general_parser.peek_current_char() == '<' {
-            return self.parse_element()
+    fn get_body(dom: &Dom) -> NodeId {
+        let html = dom.get_node(dom.get_document()).get_last_child().unwrap();
+        return get_children(dom, html)[1];
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut parser = HTMLParser::new(String::from("<html><body><h1>Title</h1><div id='main' class='test_one test_two'><p>Hello <em>world</em>!</p></div></body></html>"));
+
+        parser.parse();
+        assert!(parser.get_errors().is_empty());
+    }
+
+    #[test]
+    fn test_parse_void_elements() {
+        let mut parser = HTMLParser::new(String::from("<div>Line one<br>Line two<img src='a.png'><hr><input type='text'><meta charset='utf-8'><link rel='stylesheet' href='a.css'></div>"));
+
+        let dom = parser.parse();
+        let node = get_children(&dom, get_body(&dom))[0];
+        let children = get_children(&dom, node);
+        assert_eq!(8, children.len());
+
+        for child in &children {
+            assert_eq!(None, dom.get_node(*child).get_first_child());
         }
-        else {
-            return self.parse_text()
+
+        match &dom.get_node(children[3]).node_type {
+            NodeType::Element(element_data) => {
+                assert_eq!("img", element_data.tag_name);
+                assert_eq!(Some(&String::from("a.png")), element_data.get_attribute("src"));
+            },
+            _ => panic!("Expected an img element. ")
         }
     }
 
-    pub fn parse_attributes(&mut self) -> HashMap<String, String> {
-        let mut name_value_pairs = HashMap::new();
+    #[test]
+    fn test_parse_self_closing_elements() {
+        let mut parser = HTMLParser::new(String::from("<div><br/><span class='empty' /><p>Text</p></div>"));
 
-        while self.general_parser.peek_current_char() != '>' {
-            self.general_parser.consume_whitespaces();
-            let (name, value) = self.parse_attribute();
-            name_value_pairs.insert(name, value);
+        let dom = parser.parse();
+        let node = get_children(&dom, get_body(&dom))[0];
+        let children = get_children(&dom, node);
+        assert_eq!(3, children.len());
+        assert_eq!(0, dom.children(children[0]).count());
+        assert_eq!(0, dom.children(children[1]).count());
+        assert_eq!(1, dom.children(children[2]).count());
+    }
+
+    #[test]
+    fn test_parse_comments_and_doctype() {
+        let mut parser = HTMLParser::new(String::from("<?xml version=\"1.0\"?><!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\" 'http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd'><!-- header --><html><body><!--<p>hidden</p>--></body></html>"));
+
+        let dom = parser.parse();
+        let children = get_children(&dom, dom.get_document());
+        assert_eq!(4, children.len());
+
+        match &dom.get_node(children[0]).node_type {
+            NodeType::Comment(comment) => assert_eq!("xml version=\"1.0\"?", comment),
+            _ => panic!("Expected a comment. ")
         }
 
-        return name_value_pairs;
+        match &dom.get_node(children[1]).node_type {
+            NodeType::Doctype(doctype_data) => {
+                assert_eq!("html", doctype_data.name);
+                assert_eq!(Some(String::from("-//W3C//DTD XHTML 1.0 Strict//EN")), doctype_data.public_id);
+                assert_eq!(Some(String::from("http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd")), doctype_data.system_id);
+            },
+            _ => panic!("Expected a doctype. ")
+        }
+
+        match &dom.get_node(children[2]).node_type {
+            NodeType::Comment(comment) => assert_eq!(" header ", comment),
+            _ => panic!("Expected a comment. ")
+        }
+
+        let body = get_body(&dom);
+        match &dom.get_node(get_children(&dom, body)[0]).node_type {
+            NodeType::Comment(comment) => assert_eq!("<p>hidden</p>", comment),
+            _ => panic!("Expected a comment. ")
+        }
     }
 
-    pub fn parse_attribute(&mut self) -> (String, String) {
-        println!("{}", self.general_parser.content[self.general_parser.current_position..].to_string());
-        let name= self.parse_tag_name();
-        assert_eq!(self.general_parser.pop_current_char(), '=');
+    #[test]
+    fn test_parse_character_references() {
+        let mut parser = HTMLParser::new(String::from("<a href='?a=1&amp;b=2&copy=3' title='&lt;&#169;&gt;'>Fish &amp; Chips &#x1F4A3;</a>"));
+
+        let dom = parser.parse();
+        let node = get_children(&dom, get_body(&dom))[0];
 
-        let open_quote = self.general_parser.pop_current_char();
-        assert!(open_quote == '"' || open_quote == '\'');
-        let value = self.general_parser.consume_while(|char| char != open_quote);
-        assert_eq!(self.general_parser.pop_current_char(), open_quote);
+        match &dom.get_node(node).node_type {
+            NodeType::Element(element_data) => {
+                assert_eq!(Some(&String::from("?a=1&b=2&copy=3")), element_data.get_attribute("href"));
+                assert_eq!(Some(&String::from("<\u{A9}>")), element_data.get_attribute("title"));
+            },
+            _ => panic!("Expected an element. ")
+        }
 
-        return (name, value);
+        match &dom.get_node(get_children(&dom, node)[0]).node_type {
+            NodeType::Text(text) => assert_eq!("Fish & Chips \u{1F4A3}", text),
+            _ => panic!("Expected a text node. ")
+        }
     }
 
-    pub fn parse_element(&mut self) -> Node {
-        assert_eq!(self.general_parser.pop_current_char(), '<');
-        let tag_name = self.parse_tag_name();
-        let attributes = self.parse_attributes();
-        assert_eq!(self.general_parser.pop_current_char(), '>');
+    #[test]
+    fn test_parse_unquoted_attribute_before_self_closing() {
+        let mut parser = HTMLParser::new(String::from("<p><input disabled/><img src=a.png alt=x></p>"));
+
+        let dom = parser.parse();
+        let node = get_children(&dom, get_body(&dom))[0];
+        let children = get_children(&dom, node);
+        assert_eq!(2, children.len());
+
+        match (&dom.get_node(children[0]).node_type, &dom.get_node(children[1]).node_type) {
+            (NodeType::Element(input), NodeType::Element(img)) => {
+                assert_eq!(vec![(String::from("disabled"), String::new())], *input.get_attributes());
+                assert_eq!(Some(&String::from("a.png")), img.get_attribute("src"));
+                assert_eq!(Some(&String::from("x")), img.get_attribute("alt"));
+            },
+            _ => panic!("Expected two elements. ")
+        }
+    }
 
-        let children = self.parse_nodes();
+    #[test]
+    fn test_parse_recovers_from_invalid_markup() {
+        let mut parser = HTMLParser::new(String::from("<title>Page</title><p>one<p>two</div><ul><li>a<li>b</ul><b>c<i>d</b>e</p></body>f"));
 
-        assert_eq!(self.general_parser.pop_current_char(), '<');
-        assert_eq!(self.general_parser.pop_current_char(), '/');
-        assert_eq!(self.parse_tag_name(), tag_name);
-        assert_eq!(self.general_parser.pop_current_char(), '>');
+        let dom = parser.parse();
+        let html = get_children(&dom, dom.get_document())[0];
+        let html_children = get_children(&dom, html);
+        assert_eq!(2, html_children.len());
+        assert_eq!(1, dom.children(html_children[0]).count());
 
-        return Node::new_element(tag_name, attributes, children);
+        let body_children = get_children(&dom, get_body(&dom));
+        let tag_names: Vec<&str> = body_children.iter().map(|child| {
+            match &dom.get_node(*child).node_type {
+                NodeType::Element(element_data) => element_data.tag_name.as_str(),
+                NodeType::Text(text) => text.as_str(),
+                _ => "?"
+            }
+        }).collect();
+        assert_eq!(vec!["p", "p", "ul", "b", "e", "p", "f"], tag_names);
+        assert_eq!(2, dom.children(body_children[2]).count());
     }
 
-    pub fn parse_text(&mut self) -> Node {
-        let text = self.general_parser.consume_while(|char| char != '<');
-        return Node::new_text(text);
+    #[test]
+    fn test_parse_reports_errors_with_position() {
+        let mut parser = HTMLParser::new(String::from("<html>\n  <body>\n    <a href='unterminated>text</a>"));
+
+        let dom = parser.parse();
+        assert_eq!(1, parser.get_errors().len());
+        assert_eq!(ParseErrorKind::UnexpectedEof, parser.get_errors()[0].kind);
+        assert_eq!((3, 35), (parser.get_errors()[0].line, parser.get_errors()[0].column));
+
+        // The unterminated tag is dropped, and the tree built before it is kept.
+        assert_eq!("\n    ", dom.get_inner_html(get_body(&dom)));
+
+        let mut parser = HTMLParser::new(String::from("<p>text<!-- unterminated"));
+
+        let dom = parser.parse();
+        assert_eq!(24..24, parser.get_errors()[0].span);
+        assert_eq!("<p>text<!-- unterminated--></p>", dom.get_inner_html(get_body(&dom)));
     }
 
-    pub fn parse(&mut self) -> Node {
-        let mut nodes = self.parse_nodes();
+    #[test]
+    fn test_parse_recovers_from_end_of_input() {
+        let cases = [
+            ("<p>a<div", "<p>a</p>"),
+            ("<p>a<a href='x", "<p>a</p>"),
+            ("<p>a<br/", "<p>a</p>"),
+            ("<p>a</", "<p>a&lt;/</p>"),
+            ("<p>a<script>b</script", "<p>a<script>b</script></p>"),
+            ("<p>a</p", "<p>a</p>")
+        ];
 
-        if nodes.len() == 1 {
-            return nodes.swap_remove(0);
-        }
-        else {
-            return Node::new_element("html_parser".to_string(), HashMap::new(), nodes);
+        for (html, body_html) in cases {
+            let mut parser = HTMLParser::new(String::from(html));
+            let dom = parser.parse();
+            assert_eq!(body_html, dom.get_inner_html(get_body(&dom)), "{}", html);
+            assert_eq!(ParseErrorKind::UnexpectedEof, parser.get_errors()[0].kind, "{}", html);
         }
+
+        // An unterminated doctype is kept, but forces quirks mode.
+        let mut parser = HTMLParser::new(String::from("<!DOCTYPE html"));
+        let dom = parser.parse();
+        assert_eq!(QuirksMode::Quirks, dom.get_quirks_mode());
+        assert_eq!(1, parser.get_errors().len());
+        assert!(matches!(dom.get_node(get_children(&dom, dom.get_document())[0]).node_type, NodeType::Doctype(_)));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::general_parser::GeneralParser;
-    use crate::html_parser::HTMLParser;
+    #[test]
+    fn test_parse_normalizes_case() {
+        let mut parser = HTMLParser::new(String::from("<!DOCTYPE html><DIV ID='Main' Class=A><P>one</DIV><Script>a</SCRIPT>"));
+
+        let dom = parser.parse();
+        assert_eq!(QuirksMode::NoQuirks, dom.get_quirks_mode());
+        assert_eq!(
+            "<div id=\"Main\" class=\"A\"><p>one</p></div><script>a</script>",
+            dom.get_inner_html(get_body(&dom))
+        );
+    }
 
     #[test]
-    fn test_parse() {
-        let mut parser = HTMLParser {
-            general_parser: GeneralParser {
-                content: String::from("<html><body><h1>Title</h1><div id='main' class='test_one test_two'><p>Hello <em>world</em>!</p></div></body></html>"),
-                current_position: 0
+    fn test_parse_detects_quirks_mode() {
+        let dom = HTMLParser::new(String::from("<p>no doctype")).parse();
+        assert_eq!(QuirksMode::Quirks, dom.get_quirks_mode());
+
+        let dom = HTMLParser::new(String::from("<!-- a --><!DOCTYPE HTML PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\">")).parse();
+        assert_eq!(QuirksMode::LimitedQuirks, dom.get_quirks_mode());
+
+        // A doctype after other content is ignored.
+        let dom = HTMLParser::new(String::from("<p><!DOCTYPE html>")).parse();
+        assert_eq!(QuirksMode::Quirks, dom.get_quirks_mode());
+    }
+
+    #[test]
+    fn test_parse_records_source_spans() {
+        let mut parser = HTMLParser::new(String::from("<!DOCTYPE html>\n<p>one\n<em>two</em>\n<p>three<ul><li>a</ul>"));
+        parser.set_file_id(4);
+        let dom = parser.parse();
+        let get_span = |node_id: NodeId| dom.get_node(node_id).get_source_span().map(|span| format!("{}", span));
+
+        let doctype = dom.get_node(dom.get_document()).get_first_child().unwrap();
+        assert_eq!(Some(String::from("4:1:1-1:16")), get_span(doctype));
+
+        // <html>, <head> and <body> are implied, and so have no span.
+        let body = get_body(&dom);
+        assert_eq!(None, get_span(body));
+
+        // An element closed by its end tag spans up to the end tag; one closed implicitly ends with its start tag.
+        let children = get_children(&dom, body);
+        assert_eq!(Some(String::from("4:2:1-2:4")), get_span(children[0]));
+        assert_eq!(Some(String::from("4:3:1-3:13")), get_span(get_children(&dom, children[0])[1]));
+        assert_eq!(Some(String::from("4:4:9-4:23")), get_span(children[2]));
+
+        // Text spans cover the text as written in the source.
+        assert_eq!(Some(String::from("4:2:4-3:1")), get_span(get_children(&dom, children[0])[0]));
+    }
+
+    #[test]
+    fn test_parse_from_bytes() {
+        let mut parser = HTMLParser::from_bytes(b"<meta charset='iso-8859-1'><p>Caf\xE9 \x80");
+        assert_eq!(CharacterEncoding::Windows1252, parser.get_encoding());
+
+        let dom = parser.parse();
+        let paragraph = get_children(&dom, get_body(&dom))[0];
+        assert_eq!("<p>Caf\u{E9} \u{20AC}</p>", dom.get_outer_html(paragraph));
+    }
+
+    #[test]
+    fn test_parse_raw_text_and_rcdata_elements() {
+        let mut parser = HTMLParser::new(String::from("<title>A &amp; <b>B</b></title><style>a > b { color: red; }</style><script>if (a < b) { s = '</div>'; } </scripts> </SCRIPT><textarea>&lt;p&gt; <p></textarea >"));
+
+        let dom = parser.parse();
+        let html = get_children(&dom, dom.get_document())[0];
+        let head = get_children(&dom, html)[0];
+        let body = get_children(&dom, html)[1];
+
+        let texts: Vec<&str> = dom.children(head).chain(dom.children(body)).map(|element| {
+            let children = get_children(&dom, element);
+            assert_eq!(1, children.len());
+
+            match &dom.get_node(children[0]).node_type {
+                NodeType::Text(text) => text.as_str(),
+                _ => panic!("Expected a single text child. ")
             }
-        };
+        }).collect();
 
-        let node = parser.parse();
+        assert_eq!(
+            vec![
+                "A & <b>B</b>",
+                "a > b { color: red; }",
+                "if (a < b) { s = '</div>'; } </scripts> ",
+                "<p> <p>"
+            ],
+            texts
+        );
     }
-}
\ No newline at end of file
+}
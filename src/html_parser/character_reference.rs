@@ -0,0 +1,142 @@
+use crate::encoding::WINDOWS_1252_REPLACEMENTS;
+use crate::html_parser::named_character_references::NAMED_CHARACTER_REFERENCES;
+
+// The longest name in the named character reference table, including its semicolon.
+const LONGEST_NAME_LENGTH: usize = 32;
+
+pub fn decode_character_references(text: &str, is_attribute_value: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut position = 0;
+
+    while let Some(offset) = text[position..].find('&') {
+        result.push_str(&text[position..position + offset]);
+        position += offset + 1;
+
+        let decoded = if text[position..].starts_with('#') {
+            decode_numeric_character_reference(&text[position + 1..])
+                .map(|(char, length)| (char.to_string(), length + 1))
+        } else {
+            decode_named_character_reference(&text[position..], is_attribute_value)
+                .map(|(value, length)| (value.to_string(), length))
+        };
+
+        match decoded {
+            Some((value, length)) => {
+                result.push_str(value.as_str());
+                position += length;
+            },
+            None => result.push('&')
+        }
+    }
+
+    result.push_str(&text[position..]);
+    return result;
+}
+
+// Decodes the reference following "&#", returning the character and the number of bytes consumed.
+fn decode_numeric_character_reference(text: &str) -> Option<(char, usize)> {
+    let (radix, prefix_length) = match text.chars().next() {
+        Some('x') | Some('X') => (16, 1),
+        _ => (10, 0)
+    };
+
+    let digits_length = text[prefix_length..]
+        .find(|char: char| !char.is_digit(radix))
+        .unwrap_or(text.len() - prefix_length);
+
+    if digits_length == 0 {
+        return None;
+    }
+
+    let digits = &text[prefix_length..prefix_length + digits_length];
+    let code_point = digits.chars().fold(0u32, |acc, char| {
+        acc.saturating_mul(radix).saturating_add(char.to_digit(radix).unwrap())
+    });
+
+    let mut length = prefix_length + digits_length;
+    if text[length..].starts_with(';') {
+        length += 1;
+    }
+
+    return Some((convert_code_point(code_point), length));
+}
+
+fn convert_code_point(code_point: u32) -> char {
+    if code_point == 0 {
+        return '\u{FFFD}';
+    }
+
+    // Numeric references in the 0x80..=0x9F range are interpreted as Windows-1252,
+    // because that is what legacy pages meant when they wrote them.
+    if let Some((_, replacement)) = WINDOWS_1252_REPLACEMENTS.iter().find(|(from, _)| *from == code_point) {
+        return *replacement;
+    }
+
+    // Surrogates and values beyond the Unicode range are rejected by char::from_u32.
+    return char::from_u32(code_point).unwrap_or('\u{FFFD}');
+}
+
+// Decodes the reference following "&", returning the replacement text and the number of bytes consumed.
+fn decode_named_character_reference(text: &str, is_attribute_value: bool) -> Option<(&'static str, usize)> {
+    let candidate_length = text
+        .char_indices()
+        .take_while(|(index, char)| *index < LONGEST_NAME_LENGTH && (char.is_ascii_alphanumeric() || *char == ';'))
+        .map(|(index, char)| index + char.len_utf8())
+        .last()?;
+
+    // The longest name that matches wins, e.g. "&notin;" is "∉" but "&notit;" is "¬it;".
+    for length in (1..=candidate_length).rev() {
+        let name = &text[..length];
+
+        if let Ok(index) = NAMED_CHARACTER_REFERENCES.binary_search_by(|(entry_name, _)| entry_name.cmp(&name)) {
+            let next_char = text[length..].chars().next();
+
+            // In attribute values, legacy references without a semicolon are left alone when they
+            // look like part of a query string, e.g. href="?a=1&copy=2".
+            if is_attribute_value && !name.ends_with(';') {
+                if let Some(next_char) = next_char {
+                    if next_char == '=' || next_char.is_ascii_alphanumeric() {
+                        return None;
+                    }
+                }
+            }
+
+            return Some((NAMED_CHARACTER_REFERENCES[index].1, length));
+        }
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::character_reference::decode_character_references;
+
+    #[test]
+    fn test_decode_named_character_references() {
+        assert_eq!("a & b < c > d", decode_character_references("a &amp; b &lt; c &gt; d", false));
+        assert_eq!("\u{2208}\u{2209}", decode_character_references("&isin;&notin;", false));
+        assert_eq!("\u{AC}it;", decode_character_references("&notit;", false));
+        assert_eq!("\u{A9} 2024", decode_character_references("&copy 2024", false));
+        assert_eq!("&unknown; & &;", decode_character_references("&unknown; & &;", false));
+        assert_eq!("\u{2AB0}\u{338}", decode_character_references("&nsucceq;", false));
+    }
+
+    #[test]
+    fn test_decode_numeric_character_references() {
+        assert_eq!("\u{A9}\u{1F4A3}", decode_character_references("&#169;&#x1F4A3;", false));
+        assert_eq!("\u{A9}\u{1F4A3}", decode_character_references("&#169&#X1f4a3", false));
+        assert_eq!("\u{20AC}\u{2122}", decode_character_references("&#128;&#x99;", false));
+        assert_eq!("\u{FFFD}\u{FFFD}\u{FFFD}", decode_character_references("&#0;&#xD800;&#x110000;", false));
+        assert_eq!("\u{FFFD}", decode_character_references("&#99999999999999999999;", false));
+        assert_eq!("&#; &#x;", decode_character_references("&#; &#x;", false));
+    }
+
+    #[test]
+    fn test_decode_character_references_in_attribute_value() {
+        assert_eq!("?a=1&copy=2", decode_character_references("?a=1&copy=2", true));
+        assert_eq!("?a=1&copyb", decode_character_references("?a=1&copyb", true));
+        assert_eq!("\u{A9} \u{A9}=", decode_character_references("&copy &copy;=", true));
+        assert_eq!("a&b", decode_character_references("a&amp;b", true));
+    }
+}
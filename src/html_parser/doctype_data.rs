@@ -0,0 +1,15 @@
+pub struct DoctypeData {
+    pub name: String,
+    pub public_id: Option<String>,
+    pub system_id: Option<String>
+}
+
+impl DoctypeData {
+    pub fn new(name: String, public_id: Option<String>, system_id: Option<String>) -> Self {
+        Self {
+            name,
+            public_id,
+            system_id
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::html_parser::doctype_data::DoctypeData;
+use crate::html_parser::element_data::ElementData;
+use crate::html_parser::mutation_record::MutationRecord;
+use crate::html_parser::node::Node;
+use crate::html_parser::node_id::NodeId;
+use crate::html_parser::node_iterator::{Ancestors, Children, Descendants, PreviousSiblings};
+use crate::html_parser::node_type::NodeType;
+use crate::html_parser::quirks_mode::QuirksMode;
+use crate::html_parser::serializer::{serialize_children, serialize_node, serialize_node_pretty};
+
+// Owns every node of a document. Nodes refer to each other through NodeId handles,
+// so the tree can be navigated in any direction.
+pub struct Dom {
+    // Nodes are never freed; a removed node stays here, detached, and can be inserted again.
+    nodes: Vec<Node>,
+    pub(crate) quirks_mode: QuirksMode,
+    pub(crate) mutation_records: Vec<MutationRecord>
+}
+
+impl Dom {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::new(NodeType::Document)],
+            quirks_mode: QuirksMode::NoQuirks,
+            mutation_records: Vec::new()
+        }
+    }
+
+    pub fn get_document(&self) -> NodeId {
+        return NodeId(0);
+    }
+
+    pub fn get_quirks_mode(&self) -> QuirksMode {
+        return self.quirks_mode;
+    }
+
+    pub fn get_node(&self, node_id: NodeId) -> &Node {
+        return &self.nodes[node_id.0];
+    }
+
+    pub(crate) fn get_node_mut(&mut self, node_id: NodeId) -> &mut Node {
+        return &mut self.nodes[node_id.0];
+    }
+
+    pub fn create_node(&mut self, node_type: NodeType) -> NodeId {
+        self.nodes.push(Node::new(node_type));
+        return NodeId(self.nodes.len() - 1);
+    }
+
+    pub fn create_element(&mut self, tag_name: String, attributes: Vec<(String, String)>) -> NodeId {
+        return self.create_node(NodeType::Element(ElementData::new(tag_name, attributes)));
+    }
+
+    pub fn create_text(&mut self, text: String) -> NodeId {
+        return self.create_node(NodeType::Text(text));
+    }
+
+    pub fn create_comment(&mut self, comment: String) -> NodeId {
+        return self.create_node(NodeType::Comment(comment));
+    }
+
+    pub fn create_doctype(&mut self, name: String, public_id: Option<String>, system_id: Option<String>) -> NodeId {
+        return self.create_node(NodeType::Doctype(DoctypeData::new(name, public_id, system_id)));
+    }
+
+    // Links a detached node into the tree before reference, or as the last child when there is
+    // no reference. Unlike the public mutation methods, it neither validates nor records anything.
+    pub(crate) fn insert_node(&mut self, parent: NodeId, node: NodeId, reference: Option<NodeId>) {
+        let previous_sibling = match reference {
+            Some(reference) => self.get_node(reference).previous_sibling,
+            None => self.get_node(parent).last_child
+        };
+
+        let child_node = self.get_node_mut(node);
+        child_node.parent = Some(parent);
+        child_node.previous_sibling = previous_sibling;
+        child_node.next_sibling = reference;
+
+        match previous_sibling {
+            Some(previous_sibling) => self.get_node_mut(previous_sibling).next_sibling = Some(node),
+            None => self.get_node_mut(parent).first_child = Some(node)
+        }
+
+        match reference {
+            Some(reference) => self.get_node_mut(reference).previous_sibling = Some(node),
+            None => self.get_node_mut(parent).last_child = Some(node)
+        }
+    }
+
+    // Unlinks a node from its parent and siblings, keeping its own subtree intact.
+    pub(crate) fn detach_node(&mut self, node: NodeId) {
+        let Node { parent, previous_sibling, next_sibling, .. } = *self.get_node(node);
+
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return
+        };
+
+        match previous_sibling {
+            Some(previous_sibling) => self.get_node_mut(previous_sibling).next_sibling = next_sibling,
+            None => self.get_node_mut(parent).first_child = next_sibling
+        }
+
+        match next_sibling {
+            Some(next_sibling) => self.get_node_mut(next_sibling).previous_sibling = previous_sibling,
+            None => self.get_node_mut(parent).last_child = previous_sibling
+        }
+
+        let node = self.get_node_mut(node);
+        node.parent = None;
+        node.previous_sibling = None;
+        node.next_sibling = None;
+    }
+
+    pub fn children(&self, node_id: NodeId) -> Children<'_> {
+        return Children {
+            dom: self,
+            next: self.get_node(node_id).first_child
+        };
+    }
+
+    pub fn ancestors(&self, node_id: NodeId) -> Ancestors<'_> {
+        return Ancestors {
+            dom: self,
+            next: self.get_node(node_id).parent
+        };
+    }
+
+    pub fn previous_siblings(&self, node_id: NodeId) -> PreviousSiblings<'_> {
+        return PreviousSiblings {
+            dom: self,
+            next: self.get_node(node_id).previous_sibling
+        };
+    }
+
+    pub fn descendants(&self, node_id: NodeId) -> Descendants<'_> {
+        return Descendants {
+            dom: self,
+            root: node_id,
+            next: self.get_node(node_id).first_child
+        };
+    }
+
+    pub fn get_element_data(&self, node_id: NodeId) -> Option<&ElementData> {
+        return self.get_node(node_id).get_element_data();
+    }
+
+    pub fn get_outer_html(&self, node_id: NodeId) -> String {
+        let mut output = String::new();
+        serialize_node(self, node_id, &mut output);
+        return output;
+    }
+
+    pub fn get_inner_html(&self, node_id: NodeId) -> String {
+        let mut output = String::new();
+        serialize_children(self, node_id, &mut output);
+        return output;
+    }
+
+    // Serializes the node with one node per line and nested nodes indented, for debugging.
+    pub fn get_pretty_html(&self, node_id: NodeId) -> String {
+        let mut output = String::new();
+        serialize_node_pretty(self, node_id, 0, &mut output);
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::node_id::NodeId;
+
+    // Builds <div><p>a</p><!--b--><span></span></div> and returns the ids of all its nodes.
+    fn create_dom() -> (Dom, Vec<NodeId>) {
+        let mut dom = Dom::new();
+        let div = dom.create_element(String::from("div"), Vec::new());
+        let p = dom.create_element(String::from("p"), Vec::new());
+        let text = dom.create_text(String::from("a"));
+        let comment = dom.create_comment(String::from("b"));
+        let span = dom.create_element(String::from("span"), Vec::new());
+
+        dom.append_child(dom.get_document(), div).unwrap();
+        dom.append_child(div, p).unwrap();
+        dom.append_child(p, text).unwrap();
+        dom.append_child(div, comment).unwrap();
+        dom.append_child(div, span).unwrap();
+
+        return (dom, vec![div, p, text, comment, span]);
+    }
+
+    #[test]
+    fn test_append_child() {
+        let (dom, ids) = create_dom();
+        let (div, p, text, comment, span) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+
+        assert_eq!(Some(dom.get_document()), dom.get_node(div).get_parent());
+        assert_eq!(Some(p), dom.get_node(div).get_first_child());
+        assert_eq!(Some(span), dom.get_node(div).get_last_child());
+        assert_eq!(None, dom.get_node(p).get_previous_sibling());
+        assert_eq!(Some(comment), dom.get_node(p).get_next_sibling());
+        assert_eq!(Some(p), dom.get_node(comment).get_previous_sibling());
+        assert_eq!(None, dom.get_node(span).get_next_sibling());
+        assert_eq!(Some(text), dom.get_node(p).get_first_child());
+        assert_eq!(Some(text), dom.get_node(p).get_last_child());
+    }
+
+    #[test]
+    fn test_children() {
+        let (dom, ids) = create_dom();
+        assert_eq!(vec![ids[1], ids[3], ids[4]], dom.children(ids[0]).collect::<Vec<NodeId>>());
+        assert_eq!(0, dom.children(ids[4]).count());
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let (dom, ids) = create_dom();
+        assert_eq!(vec![ids[1], ids[0], dom.get_document()], dom.ancestors(ids[2]).collect::<Vec<NodeId>>());
+        assert_eq!(0, dom.ancestors(dom.get_document()).count());
+    }
+
+    #[test]
+    fn test_previous_siblings() {
+        let (dom, ids) = create_dom();
+        assert_eq!(vec![ids[3], ids[1]], dom.previous_siblings(ids[4]).collect::<Vec<NodeId>>());
+        assert_eq!(0, dom.previous_siblings(ids[1]).count());
+    }
+
+    #[test]
+    fn test_descendants() {
+        let (dom, ids) = create_dom();
+        assert_eq!(ids, dom.descendants(dom.get_document()).collect::<Vec<NodeId>>());
+        assert_eq!(vec![ids[2]], dom.descendants(ids[1]).collect::<Vec<NodeId>>());
+        assert_eq!(0, dom.descendants(ids[4]).count());
+    }
+}
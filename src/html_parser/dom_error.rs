@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter};
+use crate::html_parser::node_id::NodeId;
+
+#[derive(Debug, PartialEq)]
+pub enum DomError {
+    // The node that was passed as a child is not a child of the given parent.
+    NotFound(NodeId),
+    // The insertion would create an invalid tree, e.g. put a node inside itself.
+    HierarchyRequest(NodeId),
+    // The operation needs an element, such as setting an attribute on a text node.
+    InvalidNodeType(NodeId)
+}
+
+impl Display for DomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomError::NotFound(node_id) => write!(f, "node {:?} is not a child of the given parent", node_id),
+            DomError::HierarchyRequest(node_id) => write!(f, "node {:?} cannot be inserted at this position", node_id),
+            DomError::InvalidNodeType(node_id) => write!(f, "node {:?} does not support this operation", node_id)
+        }
+    }
+}
+
+impl std::error::Error for DomError {}
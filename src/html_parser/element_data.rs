@@ -1,51 +1,135 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fmt::{Display};
 
 pub struct ElementData {
     pub tag_name: String,
-    pub(crate) attributes: HashMap<String, String>
+    // Attributes in source order; names are unique.
+    attributes: Vec<(String, String)>,
+    // Cached from the id and class attributes, which selectors look up for every element.
+    id: Option<String>,
+    classes: HashSet<String>
 }
 
 impl ElementData {
-    pub fn new(tag_name: String, attributes: HashMap<String, String>) -> Self {
-        Self {
+    pub fn new(tag_name: String, attributes: Vec<(String, String)>) -> Self {
+        let mut element_data = Self {
             tag_name,
-            attributes
-        }
+            attributes,
+            id: None,
+            classes: HashSet::new()
+        };
+        element_data.update_caches();
+        return element_data;
+    }
+
+    // Attribute names are ASCII case-insensitive; they are stored in lowercase.
+    pub fn get_attribute(&self, name: &str) -> Option<&String> {
+        return self.attributes
+            .iter()
+            .find(|(attribute_name, _)| attribute_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value);
+    }
+
+    pub fn get_attributes(&self) -> &Vec<(String, String)> {
+        return &self.attributes;
     }
 
     pub fn get_id(&self) -> Option<&String> {
-        return self.attributes.get("id");
+        return self.id.as_ref();
+    }
+
+    pub fn get_classes(&self) -> &HashSet<String> {
+        return &self.classes;
+    }
+
+    // Sets an attribute, keeping its position if it already exists, and returns the old value.
+    pub fn set_attribute(&mut self, name: String, value: String) -> Option<String> {
+        let name = name.to_ascii_lowercase();
+        let old_value = match self.attributes.iter_mut().find(|(attribute_name, _)| *attribute_name == name) {
+            Some((_, existing_value)) => Some(std::mem::replace(existing_value, value)),
+            None => {
+                self.attributes.push((name, value));
+                None
+            }
+        };
+
+        self.update_caches();
+        return old_value;
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(attribute_name, _)| attribute_name.eq_ignore_ascii_case(name))?;
+        let (_, old_value) = self.attributes.remove(index);
+
+        self.update_caches();
+        return Some(old_value);
     }
 
-    pub fn get_classes(&self) -> HashSet<&str> {
-        match self.attributes.get("class") {
-            Some(classes) => classes.split(" ").collect(),
-            _ => HashSet::new()
-        }
+    fn update_caches(&mut self) {
+        self.id = self.get_attribute("id").cloned();
+        self.classes = match self.get_attribute("class") {
+            Some(classes) => classes.split_ascii_whitespace().map(String::from).collect(),
+            None => HashSet::new()
+        };
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
     use crate::html_parser::element_data::ElementData;
 
     #[test]
     fn test_get_classes() {
-        let element_data = ElementData {
-            tag_name: String::from("div"),
-            attributes: HashMap::from(
-                [
-                    (String::from("class"), String::from("class_one class_two class_three")),
-                    (String::from("name"), String::from("abc"))
-                ]
-            )
-        };
+        let element_data = ElementData::new(String::from("div"), vec![
+            (String::from("class"), String::from("class_one class_two class_three")),
+            (String::from("name"), String::from("abc"))
+        ]);
 
         assert_eq!(3, element_data.get_classes().len());
         assert!(element_data.get_classes().contains("class_one"));
         assert!(element_data.get_classes().contains("class_two"));
         assert!(element_data.get_classes().contains("class_three"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_attribute() {
+        let element_data = ElementData::new(String::from("input"), vec![
+            (String::from("type"), String::from("checkbox")),
+            (String::from("disabled"), String::new())
+        ]);
+
+        assert_eq!(Some(&String::from("checkbox")), element_data.get_attribute("type"));
+        assert_eq!(Some(&String::new()), element_data.get_attribute("disabled"));
+        assert_eq!(None, element_data.get_attribute("value"));
+    }
+
+    #[test]
+    fn test_set_and_remove_attribute() {
+        let mut element_data = ElementData::new(String::from("div"), vec![
+            (String::from("id"), String::from("a")),
+            (String::from("title"), String::from("x"))
+        ]);
+
+        assert_eq!(Some(String::from("a")), element_data.set_attribute(String::from("id"), String::from("b")));
+        assert_eq!(None, element_data.set_attribute(String::from("class"), String::from("one  two")));
+        assert_eq!(Some(&String::from("b")), element_data.get_id());
+        assert!(element_data.get_classes().contains("two"));
+        assert_eq!(
+            vec![
+                (String::from("id"), String::from("b")),
+                (String::from("title"), String::from("x")),
+                (String::from("class"), String::from("one  two"))
+            ],
+            *element_data.get_attributes()
+        );
+
+        assert_eq!(Some(&String::from("x")), element_data.get_attribute("TITLE"));
+        assert_eq!(Some(String::from("x")), element_data.set_attribute(String::from("Title"), String::from("y")));
+        assert_eq!(Some(String::from("y")), element_data.remove_attribute("TITLE"));
+        assert_eq!(Some(String::from("b")), element_data.remove_attribute("id"));
+        assert_eq!(Some(String::from("one  two")), element_data.remove_attribute("class"));
+        assert_eq!(None, element_data.remove_attribute("class"));
+        assert_eq!(None, element_data.get_id());
+        assert!(element_data.get_classes().is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+use crate::html_parser::dom::Dom;
+use crate::html_parser::dom_error::DomError;
+use crate::html_parser::mutation_record::MutationRecord;
+use crate::html_parser::node_id::NodeId;
+use crate::html_parser::node_type::NodeType;
+
+// Changes to a parsed document. Every change is validated first, so a failed call leaves the
+// tree untouched, and is recorded so that later stages can find out what was changed.
+impl Dom {
+    pub fn append_child(&mut self, parent: NodeId, node: NodeId) -> Result<(), DomError> {
+        return self.insert_before(parent, node, None);
+    }
+
+    // Inserts node before reference, or at the end when there is no reference. A node that is
+    // already in the tree is moved.
+    pub fn insert_before(&mut self, parent: NodeId, node: NodeId, reference: Option<NodeId>) -> Result<(), DomError> {
+        self.validate_insertion(parent, node, reference)?;
+
+        // Inserting a node before itself leaves it where it is.
+        let reference = match reference {
+            Some(reference) if reference == node => self.get_node(node).get_next_sibling(),
+            _ => reference
+        };
+
+        self.remove_from_parent(node);
+
+        let previous_sibling = match reference {
+            Some(reference) => self.get_node(reference).get_previous_sibling(),
+            None => self.get_node(parent).get_last_child()
+        };
+        self.insert_node(parent, node, reference);
+
+        self.mutation_records.push(MutationRecord::ChildList {
+            target: parent,
+            added_nodes: vec![node],
+            removed_nodes: Vec::new(),
+            previous_sibling,
+            next_sibling: reference
+        });
+
+        return Ok(());
+    }
+
+    pub fn remove_child(&mut self, parent: NodeId, child: NodeId) -> Result<NodeId, DomError> {
+        if self.get_node(child).get_parent() != Some(parent) {
+            return Err(DomError::NotFound(child));
+        }
+
+        self.remove_from_parent(child);
+        return Ok(child);
+    }
+
+    // Puts node in the place of child and returns the child, which is now detached.
+    pub fn replace_child(&mut self, parent: NodeId, node: NodeId, child: NodeId) -> Result<NodeId, DomError> {
+        if self.get_node(child).get_parent() != Some(parent) {
+            return Err(DomError::NotFound(child));
+        }
+
+        self.validate_insertion(parent, node, Some(child))?;
+
+        if node == child {
+            return Ok(child);
+        }
+
+        let reference = match self.get_node(child).get_next_sibling() {
+            Some(next_sibling) if next_sibling == node => self.get_node(node).get_next_sibling(),
+            next_sibling => next_sibling
+        };
+
+        self.remove_from_parent(node);
+
+        let previous_sibling = self.get_node(child).get_previous_sibling();
+        self.detach_node(child);
+        self.insert_node(parent, node, reference);
+
+        self.mutation_records.push(MutationRecord::ChildList {
+            target: parent,
+            added_nodes: vec![node],
+            removed_nodes: vec![child],
+            previous_sibling,
+            next_sibling: reference
+        });
+
+        return Ok(child);
+    }
+
+    pub fn set_attribute(&mut self, node: NodeId, name: String, value: String) -> Result<(), DomError> {
+        let name = name.to_ascii_lowercase();
+        let element_data = match &mut self.get_node_mut(node).node_type {
+            NodeType::Element(element_data) => element_data,
+            _ => return Err(DomError::InvalidNodeType(node))
+        };
+
+        let old_value = element_data.set_attribute(name.clone(), value);
+
+        self.mutation_records.push(MutationRecord::Attributes {
+            target: node,
+            name,
+            old_value
+        });
+
+        return Ok(());
+    }
+
+    pub fn remove_attribute(&mut self, node: NodeId, name: &str) -> Result<(), DomError> {
+        let element_data = match &mut self.get_node_mut(node).node_type {
+            NodeType::Element(element_data) => element_data,
+            _ => return Err(DomError::InvalidNodeType(node))
+        };
+
+        // Removing an attribute that does not exist changes nothing, so nothing is recorded.
+        if let Some(old_value) = element_data.remove_attribute(name) {
+            self.mutation_records.push(MutationRecord::Attributes {
+                target: node,
+                name: name.to_ascii_lowercase(),
+                old_value: Some(old_value)
+            });
+        }
+
+        return Ok(());
+    }
+
+    // Replaces the children of an element with a single text node, or the data of a text or
+    // comment node. As in the DOM standard, it does nothing on documents and doctypes.
+    pub fn set_text_content(&mut self, node: NodeId, text: String) {
+        match &mut self.get_node_mut(node).node_type {
+            NodeType::Text(data) | NodeType::Comment(data) => {
+                let old_value = std::mem::replace(data, text);
+                self.mutation_records.push(MutationRecord::CharacterData {
+                    target: node,
+                    old_value
+                });
+            },
+            NodeType::Element(_) => {
+                let removed_nodes: Vec<NodeId> = self.children(node).collect();
+
+                for child in &removed_nodes {
+                    self.detach_node(*child);
+                }
+
+                let mut added_nodes = Vec::new();
+
+                if !text.is_empty() {
+                    let text = self.create_text(text);
+                    self.insert_node(node, text, None);
+                    added_nodes.push(text);
+                }
+
+                if !removed_nodes.is_empty() || !added_nodes.is_empty() {
+                    self.mutation_records.push(MutationRecord::ChildList {
+                        target: node,
+                        added_nodes,
+                        removed_nodes,
+                        previous_sibling: None,
+                        next_sibling: None
+                    });
+                }
+            },
+            NodeType::Document | NodeType::Doctype(_) => {}
+        }
+    }
+
+    // Returns the changes made since the last call, oldest first.
+    pub fn take_mutation_records(&mut self) -> Vec<MutationRecord> {
+        return std::mem::take(&mut self.mutation_records);
+    }
+
+    fn validate_insertion(&self, parent: NodeId, node: NodeId, reference: Option<NodeId>) -> Result<(), DomError> {
+        if !matches!(self.get_node(parent).node_type, NodeType::Document | NodeType::Element(_)) {
+            return Err(DomError::HierarchyRequest(parent));
+        }
+
+        // A node cannot be inserted into itself or one of its own descendants.
+        let is_ancestor_of_parent = node == parent || self.ancestors(parent).any(|ancestor| ancestor == node);
+
+        if matches!(self.get_node(node).node_type, NodeType::Document) || is_ancestor_of_parent {
+            return Err(DomError::HierarchyRequest(node));
+        }
+
+        if let Some(reference) = reference {
+            if self.get_node(reference).get_parent() != Some(parent) {
+                return Err(DomError::NotFound(reference));
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn remove_from_parent(&mut self, node: NodeId) {
+        let parent = match self.get_node(node).get_parent() {
+            Some(parent) => parent,
+            None => return
+        };
+
+        let previous_sibling = self.get_node(node).get_previous_sibling();
+        let next_sibling = self.get_node(node).get_next_sibling();
+        self.detach_node(node);
+
+        self.mutation_records.push(MutationRecord::ChildList {
+            target: parent,
+            added_nodes: Vec::new(),
+            removed_nodes: vec![node],
+            previous_sibling,
+            next_sibling
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::dom_error::DomError;
+    use crate::html_parser::mutation_record::MutationRecord;
+    use crate::html_parser::node_id::NodeId;
+
+    fn parse_document() -> (Dom, NodeId) {
+        let dom = HTMLParser::new(String::from("<div id='main'><p>a</p><p>b</p></div><script>track()</script>")).parse();
+        let main = dom.get_element_by_id(dom.get_document(), "main").unwrap();
+        return (dom, main);
+    }
+
+    #[test]
+    fn test_parsing_records_nothing() {
+        let (mut dom, _) = parse_document();
+        assert!(dom.take_mutation_records().is_empty());
+    }
+
+    #[test]
+    fn test_insert_before() {
+        let (mut dom, main) = parse_document();
+        let first = dom.get_node(main).get_first_child().unwrap();
+        let last = dom.get_node(main).get_last_child().unwrap();
+
+        let banner = dom.create_element(String::from("header"), Vec::new());
+        dom.insert_before(main, banner, Some(first)).unwrap();
+        assert_eq!("<header></header><p>a</p><p>b</p>", dom.get_inner_html(main));
+
+        // Moving a node records its removal from the old position first.
+        dom.insert_before(main, last, Some(banner)).unwrap();
+        assert_eq!("<p>b</p><header></header><p>a</p>", dom.get_inner_html(main));
+        assert_eq!(
+            vec![
+                MutationRecord::ChildList { target: main, added_nodes: vec![banner], removed_nodes: Vec::new(), previous_sibling: None, next_sibling: Some(first) },
+                MutationRecord::ChildList { target: main, added_nodes: Vec::new(), removed_nodes: vec![last], previous_sibling: Some(first), next_sibling: None },
+                MutationRecord::ChildList { target: main, added_nodes: vec![last], removed_nodes: Vec::new(), previous_sibling: None, next_sibling: Some(banner) }
+            ],
+            dom.take_mutation_records()
+        );
+        assert!(dom.take_mutation_records().is_empty());
+    }
+
+    #[test]
+    fn test_insertion_is_validated() {
+        let (mut dom, main) = parse_document();
+        let first = dom.get_node(main).get_first_child().unwrap();
+        let text = dom.get_node(first).get_first_child().unwrap();
+        let detached = dom.create_element(String::from("span"), Vec::new());
+
+        assert_eq!(Err(DomError::HierarchyRequest(main)), dom.append_child(first, main));
+        assert_eq!(Err(DomError::HierarchyRequest(main)), dom.append_child(main, main));
+        assert_eq!(Err(DomError::HierarchyRequest(text)), dom.append_child(text, detached));
+        assert_eq!(Err(DomError::NotFound(text)), dom.insert_before(main, detached, Some(text)));
+        assert_eq!(Err(DomError::NotFound(detached)), dom.remove_child(main, detached));
+        assert!(dom.take_mutation_records().is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_replace_child() {
+        let (mut dom, main) = parse_document();
+        let body = dom.get_node(main).get_parent().unwrap();
+        let script = dom.get_node(body).get_last_child().unwrap();
+        let first = dom.get_node(main).get_first_child().unwrap();
+
+        assert_eq!(Ok(script), dom.remove_child(body, script));
+        assert_eq!(None, dom.get_node(script).get_parent());
+        assert_eq!(Some(main), dom.get_node(body).get_last_child());
+
+        let text = dom.create_text(String::from("c"));
+        assert_eq!(Ok(first), dom.replace_child(main, text, first));
+        assert_eq!("c<p>b</p>", dom.get_inner_html(main));
+        assert_eq!(None, dom.get_node(first).get_parent());
+
+        let records = dom.take_mutation_records();
+        assert_eq!(2, records.len());
+        assert_eq!(
+            MutationRecord::ChildList { target: main, added_nodes: vec![text], removed_nodes: vec![first], previous_sibling: None, next_sibling: dom.get_node(text).get_next_sibling() },
+            records[1]
+        );
+    }
+
+    #[test]
+    fn test_set_and_remove_attribute() {
+        let (mut dom, main) = parse_document();
+        let text = dom.descendants(main).last().unwrap();
+
+        dom.set_attribute(main, String::from("id"), String::from("content")).unwrap();
+        dom.set_attribute(main, String::from("class"), String::from("wide")).unwrap();
+        dom.remove_attribute(main, "title").unwrap();
+        dom.remove_attribute(main, "class").unwrap();
+
+        assert_eq!(None, dom.get_element_by_id(dom.get_document(), "main"));
+        assert_eq!(Some(main), dom.get_element_by_id(dom.get_document(), "content"));
+        assert!(dom.get_elements_by_class_name(dom.get_document(), "wide").is_empty());
+        assert_eq!(Err(DomError::InvalidNodeType(text)), dom.set_attribute(text, String::from("id"), String::new()));
+        assert_eq!(
+            vec![
+                MutationRecord::Attributes { target: main, name: String::from("id"), old_value: Some(String::from("main")) },
+                MutationRecord::Attributes { target: main, name: String::from("class"), old_value: None },
+                MutationRecord::Attributes { target: main, name: String::from("class"), old_value: Some(String::from("wide")) }
+            ],
+            dom.take_mutation_records()
+        );
+    }
+
+    #[test]
+    fn test_set_text_content() {
+        let (mut dom, main) = parse_document();
+        let children: Vec<NodeId> = dom.children(main).collect();
+
+        dom.set_text_content(main, String::from("a < b"));
+        assert_eq!("a &lt; b", dom.get_inner_html(main));
+
+        let text = dom.get_node(main).get_first_child().unwrap();
+        dom.set_text_content(text, String::from("c"));
+        assert_eq!("<div id=\"main\">c</div>", dom.get_outer_html(main));
+
+        assert_eq!(
+            vec![
+                MutationRecord::ChildList { target: main, added_nodes: vec![text], removed_nodes: children, previous_sibling: None, next_sibling: None },
+                MutationRecord::CharacterData { target: text, old_value: String::from("a < b") }
+            ],
+            dom.take_mutation_records()
+        );
+    }
+}
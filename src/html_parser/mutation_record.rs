@@ -0,0 +1,25 @@
+use crate::html_parser::node_id::NodeId;
+
+// Describes one change made through the mutation methods of a Dom, in the spirit of the
+// MutationRecord of the DOM standard.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MutationRecord {
+    ChildList {
+        target: NodeId,
+        added_nodes: Vec<NodeId>,
+        removed_nodes: Vec<NodeId>,
+        // The siblings around the added or removed nodes, at the time of the change.
+        previous_sibling: Option<NodeId>,
+        next_sibling: Option<NodeId>
+    },
+    Attributes {
+        target: NodeId,
+        name: String,
+        // None when the attribute did not exist before.
+        old_value: Option<String>
+    },
+    CharacterData {
+        target: NodeId,
+        old_value: String
+    }
+}
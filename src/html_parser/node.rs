@@ -1,27 +1,61 @@
-use std::collections::HashMap;
-use std::fmt::{Display};
 use crate::html_parser::element_data::ElementData;
+use crate::html_parser::node_id::NodeId;
 use crate::html_parser::node_type::NodeType;
+use crate::source_span::SourceSpan;
 
 pub struct Node {
-    pub children: Vec<Node>,
-    pub node_type: NodeType
+    pub node_type: NodeType,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) first_child: Option<NodeId>,
+    pub(crate) last_child: Option<NodeId>,
+    pub(crate) previous_sibling: Option<NodeId>,
+    pub(crate) next_sibling: Option<NodeId>,
+    // The markup the node was parsed from. Nodes implied by the parser or created through the
+    // mutation methods have none.
+    pub(crate) source_span: Option<SourceSpan>
 }
 
 impl Node {
-    pub fn new_text(text: String) -> Self {
+    pub fn new(node_type: NodeType) -> Self {
         Self {
-            children: Vec::new(),
-            node_type: NodeType::Text(text)
+            node_type,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            previous_sibling: None,
+            next_sibling: None,
+            source_span: None
         }
     }
 
-    pub fn new_element(tag_name: String, attributes: HashMap<String, String>, children: Vec<Node>) -> Self {
-        Self {
-            children,
-            node_type: NodeType::Element(
-                ElementData::new(tag_name, attributes)
-            )
-        }
+    pub fn get_parent(&self) -> Option<NodeId> {
+        return self.parent;
+    }
+
+    pub fn get_first_child(&self) -> Option<NodeId> {
+        return self.first_child;
+    }
+
+    pub fn get_last_child(&self) -> Option<NodeId> {
+        return self.last_child;
+    }
+
+    pub fn get_previous_sibling(&self) -> Option<NodeId> {
+        return self.previous_sibling;
     }
-}
\ No newline at end of file
+
+    pub fn get_next_sibling(&self) -> Option<NodeId> {
+        return self.next_sibling;
+    }
+
+    pub fn get_source_span(&self) -> Option<SourceSpan> {
+        return self.source_span;
+    }
+
+    pub fn get_element_data(&self) -> Option<&ElementData> {
+        return match &self.node_type {
+            NodeType::Element(element_data) => Some(element_data),
+            _ => None
+        };
+    }
+}
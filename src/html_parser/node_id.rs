@@ -0,0 +1,3 @@
+// A handle to a node in a Dom. It stays valid for as long as the Dom it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(pub(crate) usize);
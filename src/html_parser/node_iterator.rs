@@ -0,0 +1,85 @@
+use crate::html_parser::dom::Dom;
+use crate::html_parser::node_id::NodeId;
+
+// Iterates over the children of a node, first to last.
+pub struct Children<'a> {
+    pub(crate) dom: &'a Dom,
+    pub(crate) next: Option<NodeId>
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        self.next = self.dom.get_node(current).get_next_sibling();
+        return Some(current);
+    }
+}
+
+// Iterates over the ancestors of a node, from its parent up to the document.
+pub struct Ancestors<'a> {
+    pub(crate) dom: &'a Dom,
+    pub(crate) next: Option<NodeId>
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        self.next = self.dom.get_node(current).get_parent();
+        return Some(current);
+    }
+}
+
+// Iterates over the siblings before a node, from the nearest one back to the first child.
+pub struct PreviousSiblings<'a> {
+    pub(crate) dom: &'a Dom,
+    pub(crate) next: Option<NodeId>
+}
+
+impl<'a> Iterator for PreviousSiblings<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        self.next = self.dom.get_node(current).get_previous_sibling();
+        return Some(current);
+    }
+}
+
+// Iterates over the descendants of a node in document order, excluding the node itself.
+pub struct Descendants<'a> {
+    pub(crate) dom: &'a Dom,
+    pub(crate) root: NodeId,
+    pub(crate) next: Option<NodeId>
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let current = self.next?;
+        let current_node = self.dom.get_node(current);
+
+        self.next = current_node.get_first_child().or_else(|| {
+            // Climb until a node with a next sibling is found, without leaving the root.
+            let mut node_id = current;
+
+            while node_id != self.root {
+                let node = self.dom.get_node(node_id);
+
+                if let Some(next_sibling) = node.get_next_sibling() {
+                    return Some(next_sibling);
+                }
+
+                node_id = node.get_parent()?;
+            }
+
+            return None;
+        });
+
+        return Some(current);
+    }
+}
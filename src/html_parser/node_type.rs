@@ -1,7 +1,11 @@
 use std::fmt::{Display};
+use crate::html_parser::doctype_data::DoctypeData;
 use crate::html_parser::element_data::ElementData;
 
 pub enum NodeType {
+    Document,
     Text(String),
-    Element(ElementData)
+    Element(ElementData),
+    Comment(String),
+    Doctype(DoctypeData)
 }
\ No newline at end of file
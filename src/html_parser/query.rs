@@ -0,0 +1,151 @@
+use crate::css_parser::CSSParser;
+use crate::css_parser::selector::Selector;
+use crate::html_parser::dom::Dom;
+use crate::html_parser::element_data::ElementData;
+use crate::html_parser::node_id::NodeId;
+use crate::parse_error::ParseError;
+use crate::style_tree_builder::check_if_selector_and_element_match;
+
+// Lookups over the descendants of a node, usually the document, in document order.
+impl Dom {
+    pub fn get_element_by_id(&self, node_id: NodeId, id: &str) -> Option<NodeId> {
+        return self.find_descendant_elements(node_id, |element_data| element_data.get_id().is_some_and(|element_id| element_id == id))
+            .next();
+    }
+
+    pub fn get_elements_by_tag_name(&self, node_id: NodeId, tag_name: &str) -> Vec<NodeId> {
+        return self.find_descendant_elements(node_id, |element_data| tag_name == "*" || element_data.tag_name.eq_ignore_ascii_case(tag_name))
+            .collect();
+    }
+
+    // Finds the elements that have all of the space-separated class names. As with selectors,
+    // class names ignore ASCII case in quirks mode.
+    pub fn get_elements_by_class_name(&self, node_id: NodeId, class_names: &str) -> Vec<NodeId> {
+        let class_names: Vec<String> = class_names.split_ascii_whitespace().map(String::from).collect();
+
+        if class_names.is_empty() {
+            return Vec::new();
+        }
+
+        let selector = Selector {
+            tag_name: None,
+            id: None,
+            classes: class_names,
+            combinator: None,
+            source_span: None
+        };
+
+        return self.descendants(node_id)
+            .filter(|descendant| check_if_selector_and_element_match(&selector, self, *descendant))
+            .collect();
+    }
+
+    pub fn query_selector(&self, node_id: NodeId, selectors: &str) -> Result<Option<NodeId>, ParseError> {
+        let selectors = CSSParser::new(selectors.to_string()).parse_selector_list()?;
+
+        return Ok(self.find_matching_descendants(node_id, &selectors).next());
+    }
+
+    pub fn query_selector_all(&self, node_id: NodeId, selectors: &str) -> Result<Vec<NodeId>, ParseError> {
+        let selectors = CSSParser::new(selectors.to_string()).parse_selector_list()?;
+
+        return Ok(self.find_matching_descendants(node_id, &selectors).collect());
+    }
+
+    // Selectors may match ancestors outside of the node, as only the element itself has to be a descendant.
+    fn find_matching_descendants<'a>(&'a self, node_id: NodeId, selectors: &'a [Selector]) -> impl Iterator<Item = NodeId> + 'a {
+        return self.descendants(node_id)
+            .filter(move |descendant| selectors.iter().any(|selector| check_if_selector_and_element_match(selector, self, *descendant)));
+    }
+
+    fn find_descendant_elements<'a, F>(&'a self, node_id: NodeId, predicate: F) -> impl Iterator<Item = NodeId> + 'a
+        where F: Fn(&ElementData) -> bool + 'a
+    {
+        return self.descendants(node_id)
+            .filter(move |descendant| self.get_element_data(*descendant).is_some_and(|element_data| predicate(element_data)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::node_id::NodeId;
+    use crate::parse_error::ParseErrorKind;
+
+    fn get_ids(dom: &Dom, node_ids: Vec<NodeId>) -> Vec<String> {
+        return node_ids.iter().map(|node_id| {
+            match dom.get_element_data(*node_id) {
+                Some(element_data) => element_data.get_id().cloned().unwrap_or_default(),
+                None => panic!("Expected an element. ")
+            }
+        }).collect();
+    }
+
+    fn parse_document() -> Dom {
+        let html = "<div id='a' class='card'><p id='b' class='title card'>x</p><p id='c' class='title'>y</p></div>\
+            <section id='d' class='card'><p id='e'>z</p><p id='a'>duplicate</p></section>";
+        return HTMLParser::new(String::from(html)).parse();
+    }
+
+    #[test]
+    fn test_get_element_by_id() {
+        let dom = parse_document();
+        let document = dom.get_document();
+        assert_eq!(vec!["a"], get_ids(&dom, dom.get_element_by_id(document, "a").into_iter().collect()));
+        assert_eq!("z", dom.get_inner_html(dom.get_element_by_id(document, "e").unwrap()));
+        assert!(dom.get_element_by_id(document, "missing").is_none());
+    }
+
+    #[test]
+    fn test_get_elements_by_tag_name() {
+        let dom = parse_document();
+        let document = dom.get_document();
+        assert_eq!(vec!["b", "c", "e", "a"], get_ids(&dom, dom.get_elements_by_tag_name(document, "p")));
+        assert_eq!(9, dom.get_elements_by_tag_name(document, "*").len());
+        assert!(dom.get_elements_by_tag_name(document, "span").is_empty());
+        assert_eq!(4, dom.get_elements_by_tag_name(document, "P").len());
+
+        let section = dom.get_element_by_id(document, "d").unwrap();
+        assert_eq!(vec!["e", "a"], get_ids(&dom, dom.get_elements_by_tag_name(section, "p")));
+    }
+
+    #[test]
+    fn test_get_elements_by_class_name() {
+        let dom = parse_document();
+        let document = dom.get_document();
+        assert_eq!(vec!["a", "b", "d"], get_ids(&dom, dom.get_elements_by_class_name(document, "card")));
+        assert_eq!(vec!["b"], get_ids(&dom, dom.get_elements_by_class_name(document, " card  title ")));
+        assert!(dom.get_elements_by_class_name(document, " ").is_empty());
+
+        // The document has no doctype, so it is in quirks mode.
+        assert_eq!(vec!["b", "c"], get_ids(&dom, dom.get_elements_by_class_name(document, "TITLE")));
+
+        let dom = HTMLParser::new(String::from("<!DOCTYPE html><p class='Title'>")).parse();
+        assert!(dom.get_elements_by_class_name(dom.get_document(), "title").is_empty());
+    }
+
+    #[test]
+    fn test_query_selector() {
+        let dom = parse_document();
+        let document = dom.get_document();
+        assert_eq!(vec!["c"], get_ids(&dom, dom.query_selector(document, "p.title#c").unwrap().into_iter().collect()));
+        assert_eq!(vec!["b"], get_ids(&dom, dom.query_selector(document, "section, .title").unwrap().into_iter().collect()));
+        assert!(dom.query_selector(document, "span").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_query_selector_all() {
+        let dom = parse_document();
+        let document = dom.get_document();
+        assert_eq!(vec!["a", "b", "c", "d"], get_ids(&dom, dom.query_selector_all(document, ".card, .title").unwrap()));
+        assert_eq!(vec!["b", "c", "e", "a"], get_ids(&dom, dom.query_selector_all(document, "p#e, p").unwrap()));
+        assert_eq!(ParseErrorKind::UnexpectedCharacter('{'), dom.query_selector_all(document, "p {").err().unwrap().kind);
+
+        assert_eq!(vec!["b", "c", "e"], get_ids(&dom, dom.query_selector_all(document, ".card > .title, section > #e").unwrap()));
+
+        // Only the element itself has to be inside the node; the selector may match its ancestors too.
+        let section = dom.get_element_by_id(document, "d").unwrap();
+        assert_eq!(vec!["e", "a"], get_ids(&dom, dom.query_selector_all(section, "body .card p").unwrap()));
+    }
+}
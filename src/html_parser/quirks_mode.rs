@@ -0,0 +1,120 @@
+// Decided by the doctype of a document. Old doctypes, or none at all, ask for the behavior
+// of browsers from before the standards, e.g. ids and classes that ignore ASCII case.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum QuirksMode {
+    NoQuirks,
+    LimitedQuirks,
+    Quirks
+}
+
+// Public identifiers of the doctypes that switch a document to quirks mode, compared by prefix.
+const QUIRKS_PUBLIC_ID_PREFIXES: [&str; 55] = [
+    "+//silmaril//dtd html pro v0r11 19970101//",
+    "-//as//dtd html 3.0 aswedit + extensions//",
+    "-//advasoft ltd//dtd html 3.0 aswedit + extensions//",
+    "-//ietf//dtd html 2.0 level 1//",
+    "-//ietf//dtd html 2.0 level 2//",
+    "-//ietf//dtd html 2.0 strict level 1//",
+    "-//ietf//dtd html 2.0 strict level 2//",
+    "-//ietf//dtd html 2.0 strict//",
+    "-//ietf//dtd html 2.0//",
+    "-//ietf//dtd html 2.1e//",
+    "-//ietf//dtd html 3.0//",
+    "-//ietf//dtd html 3.2 final//",
+    "-//ietf//dtd html 3.2//",
+    "-//ietf//dtd html 3//",
+    "-//ietf//dtd html level 0//",
+    "-//ietf//dtd html level 1//",
+    "-//ietf//dtd html level 2//",
+    "-//ietf//dtd html level 3//",
+    "-//ietf//dtd html strict level 0//",
+    "-//ietf//dtd html strict level 1//",
+    "-//ietf//dtd html strict level 2//",
+    "-//ietf//dtd html strict level 3//",
+    "-//ietf//dtd html strict//",
+    "-//ietf//dtd html//",
+    "-//metrius//dtd metrius presentational//",
+    "-//microsoft//dtd internet explorer 2.0 html strict//",
+    "-//microsoft//dtd internet explorer 2.0 html//",
+    "-//microsoft//dtd internet explorer 2.0 tables//",
+    "-//microsoft//dtd internet explorer 3.0 html strict//",
+    "-//microsoft//dtd internet explorer 3.0 html//",
+    "-//microsoft//dtd internet explorer 3.0 tables//",
+    "-//netscape comm. corp.//dtd html//",
+    "-//netscape comm. corp.//dtd strict html//",
+    "-//o'reilly and associates//dtd html 2.0//",
+    "-//o'reilly and associates//dtd html extended 1.0//",
+    "-//o'reilly and associates//dtd html extended relaxed 1.0//",
+    "-//sq//dtd html 2.0 hotmetal + extensions//",
+    "-//softquad software//dtd hotmetal pro 6.0::19990601::extensions to html 4.0//",
+    "-//softquad//dtd hotmetal pro 4.0::19971010::extensions to html 4.0//",
+    "-//spyglass//dtd html 2.0 extended//",
+    "-//sun microsystems corp.//dtd hotjava html//",
+    "-//sun microsystems corp.//dtd hotjava strict html//",
+    "-//w3c//dtd html 3 1995-03-24//",
+    "-//w3c//dtd html 3.2 draft//",
+    "-//w3c//dtd html 3.2 final//",
+    "-//w3c//dtd html 3.2//",
+    "-//w3c//dtd html 3.2s draft//",
+    "-//w3c//dtd html 4.0 frameset//",
+    "-//w3c//dtd html 4.0 transitional//",
+    "-//w3c//dtd html experimental 19960712//",
+    "-//w3c//dtd html experimental 970421//",
+    "-//w3c//dtd w3 html//",
+    "-//w3o//dtd w3 html 3.0//",
+    "-//webtechs//dtd mozilla html 2.0//",
+    "-//webtechs//dtd mozilla html//"
+];
+
+const QUIRKS_PUBLIC_IDS: [&str; 3] = ["-//w3o//dtd w3 html strict 3.0//en//", "-/w3c/dtd html 4.0 transitional/en", "html"];
+
+const QUIRKS_SYSTEM_ID: &str = "http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd";
+
+// HTML 4.01 doctypes that are quirky without a system identifier and limited-quirky with one.
+const HTML_4_01_PUBLIC_ID_PREFIXES: [&str; 2] = ["-//w3c//dtd html 4.01 frameset//", "-//w3c//dtd html 4.01 transitional//"];
+
+const LIMITED_QUIRKS_PUBLIC_ID_PREFIXES: [&str; 2] = ["-//w3c//dtd xhtml 1.0 frameset//", "-//w3c//dtd xhtml 1.0 transitional//"];
+
+impl QuirksMode {
+    pub fn from_doctype(name: &str, public_id: Option<&String>, system_id: Option<&String>, force_quirks: bool) -> Self {
+        let public_id = public_id.map_or(String::new(), |public_id| public_id.to_ascii_lowercase());
+        let has_system_id = system_id.is_some();
+        let system_id = system_id.map_or(String::new(), |system_id| system_id.to_ascii_lowercase());
+        let is_public_id_prefixed_by = |prefixes: &[&str]| prefixes.iter().any(|prefix| public_id.starts_with(prefix));
+
+        if force_quirks
+            || name != "html"
+            || QUIRKS_PUBLIC_IDS.contains(&public_id.as_str())
+            || system_id == QUIRKS_SYSTEM_ID
+            || is_public_id_prefixed_by(&QUIRKS_PUBLIC_ID_PREFIXES)
+            || (!has_system_id && is_public_id_prefixed_by(&HTML_4_01_PUBLIC_ID_PREFIXES)) {
+            return QuirksMode::Quirks;
+        }
+
+        if is_public_id_prefixed_by(&LIMITED_QUIRKS_PUBLIC_ID_PREFIXES) || is_public_id_prefixed_by(&HTML_4_01_PUBLIC_ID_PREFIXES) {
+            return QuirksMode::LimitedQuirks;
+        }
+
+        return QuirksMode::NoQuirks;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::quirks_mode::QuirksMode;
+
+    #[test]
+    fn test_from_doctype() {
+        let html_4_01 = String::from("-//W3C//DTD HTML 4.01 Transitional//EN");
+        let html_4_01_system_id = String::from("http://www.w3.org/TR/html4/loose.dtd");
+        let xhtml_strict = String::from("-//W3C//DTD XHTML 1.0 Strict//EN");
+
+        assert_eq!(QuirksMode::NoQuirks, QuirksMode::from_doctype("html", None, None, false));
+        assert_eq!(QuirksMode::NoQuirks, QuirksMode::from_doctype("html", Some(&xhtml_strict), None, false));
+        assert_eq!(QuirksMode::Quirks, QuirksMode::from_doctype("html", None, None, true));
+        assert_eq!(QuirksMode::Quirks, QuirksMode::from_doctype("svg", None, None, false));
+        assert_eq!(QuirksMode::Quirks, QuirksMode::from_doctype("html", Some(&String::from("-//IETF//DTD HTML 2.0//EN")), None, false));
+        assert_eq!(QuirksMode::Quirks, QuirksMode::from_doctype("html", Some(&html_4_01), None, false));
+        assert_eq!(QuirksMode::LimitedQuirks, QuirksMode::from_doctype("html", Some(&html_4_01), Some(&html_4_01_system_id), false));
+    }
+}
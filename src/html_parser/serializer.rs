@@ -0,0 +1,241 @@
+use crate::html_parser::{is_void_element, RAW_TEXT_ELEMENTS};
+use crate::html_parser::dom::Dom;
+use crate::html_parser::node_id::NodeId;
+use crate::html_parser::node_type::NodeType;
+
+// Elements whose leading line feed is dropped by the parser, so it has to be written twice.
+const LEADING_LINE_FEED_ELEMENTS: [&str; 3] = ["listing", "pre", "textarea"];
+
+const INDENTATION: &str = "  ";
+
+pub fn serialize_node(dom: &Dom, node_id: NodeId, output: &mut String) {
+    match &dom.get_node(node_id).node_type {
+        NodeType::Document => serialize_children(dom, node_id, output),
+        NodeType::Element(element_data) => {
+            serialize_start_tag(dom, node_id, output);
+
+            if is_void_element(element_data.tag_name.as_str()) {
+                return;
+            }
+
+            serialize_children(dom, node_id, output);
+            output.push_str(format!("</{}>", element_data.tag_name).as_str());
+        },
+        NodeType::Text(text) => output.push_str(escape_text(text.as_str()).as_str()),
+        NodeType::Comment(comment) => output.push_str(format!("<!--{}-->", comment).as_str()),
+        NodeType::Doctype(doctype_data) => {
+            output.push_str(format!("<!DOCTYPE {}", doctype_data.name).as_str());
+
+            match (&doctype_data.public_id, &doctype_data.system_id) {
+                (Some(public_id), Some(system_id)) => output.push_str(format!(" PUBLIC \"{}\" \"{}\"", public_id, system_id).as_str()),
+                (Some(public_id), None) => output.push_str(format!(" PUBLIC \"{}\"", public_id).as_str()),
+                (None, Some(system_id)) => output.push_str(format!(" SYSTEM \"{}\"", system_id).as_str()),
+                (None, None) => {}
+            }
+
+            output.push('>');
+        }
+    }
+}
+
+pub fn serialize_children(dom: &Dom, node_id: NodeId, output: &mut String) {
+    let tag_name = dom.get_element_data(node_id).map_or("", |element_data| element_data.tag_name.as_str());
+
+    if LEADING_LINE_FEED_ELEMENTS.contains(&tag_name) {
+        if let Some(first_child) = dom.get_node(node_id).get_first_child() {
+            if let NodeType::Text(text) = &dom.get_node(first_child).node_type {
+                if text.starts_with('\n') {
+                    output.push('\n');
+                }
+            }
+        }
+    }
+
+    for child in dom.children(node_id) {
+        match &dom.get_node(child).node_type {
+            NodeType::Text(text) if RAW_TEXT_ELEMENTS.contains(&tag_name) => output.push_str(text.as_str()),
+            _ => serialize_node(dom, child, output)
+        }
+    }
+}
+
+// Writes one node per line, indented by depth. Text is trimmed and white-space-only text is
+// left out, except inside elements such as <pre> and <script> whose content is written as is.
+pub fn serialize_node_pretty(dom: &Dom, node_id: NodeId, depth: usize, output: &mut String) {
+    let indentation = INDENTATION.repeat(depth);
+
+    match &dom.get_node(node_id).node_type {
+        NodeType::Document => {
+            for child in dom.children(node_id) {
+                serialize_node_pretty(dom, child, depth, output);
+            }
+        },
+        NodeType::Element(element_data) => {
+            let tag_name = element_data.tag_name.as_str();
+            output.push_str(indentation.as_str());
+
+            let keeps_content_as_is = RAW_TEXT_ELEMENTS.contains(&tag_name) || LEADING_LINE_FEED_ELEMENTS.contains(&tag_name);
+            let has_only_text = dom.children(node_id).all(|child| matches!(dom.get_node(child).node_type, NodeType::Text(_)));
+
+            if keeps_content_as_is || is_void_element(tag_name) || dom.get_node(node_id).get_first_child().is_none() {
+                serialize_node(dom, node_id, output);
+            } else if has_only_text {
+                serialize_start_tag(dom, node_id, output);
+                let mut text = String::new();
+                serialize_children(dom, node_id, &mut text);
+                output.push_str(text.trim());
+                output.push_str(format!("</{}>", tag_name).as_str());
+            } else {
+                serialize_start_tag(dom, node_id, output);
+                output.push('\n');
+
+                for child in dom.children(node_id) {
+                    serialize_node_pretty(dom, child, depth + 1, output);
+                }
+
+                output.push_str(format!("{}</{}>", indentation, tag_name).as_str());
+            }
+
+            output.push('\n');
+        },
+        NodeType::Text(text) => {
+            let text = text.trim();
+
+            if !text.is_empty() {
+                output.push_str(format!("{}{}\n", indentation, escape_text(text)).as_str());
+            }
+        },
+        NodeType::Comment(_) | NodeType::Doctype(_) => {
+            output.push_str(indentation.as_str());
+            serialize_node(dom, node_id, output);
+            output.push('\n');
+        }
+    }
+}
+
+fn serialize_start_tag(dom: &Dom, node_id: NodeId, output: &mut String) {
+    if let Some(element_data) = dom.get_element_data(node_id) {
+        output.push('<');
+        output.push_str(element_data.tag_name.as_str());
+
+        for (name, value) in element_data.get_attributes() {
+            output.push_str(format!(" {}=\"{}\"", name, escape_attribute_value(value.as_str())).as_str());
+        }
+
+        output.push('>');
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('\u{A0}', "&nbsp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+}
+
+fn escape_attribute_value(value: &str) -> String {
+    return value
+        .replace('&', "&amp;")
+        .replace('\u{A0}', "&nbsp;")
+        .replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::serializer::{escape_attribute_value, escape_text};
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!("a &lt;b&gt; &amp;amp;&nbsp;\"c\"", escape_text("a <b> &amp;\u{A0}\"c\""));
+    }
+
+    #[test]
+    fn test_escape_attribute_value() {
+        assert_eq!("a <b> &amp;amp;&nbsp;&quot;c&quot;", escape_attribute_value("a <b> &amp;\u{A0}\"c\""));
+    }
+
+    #[test]
+    fn test_get_outer_html() {
+        let html = "<!DOCTYPE html><html lang=\"en\"><head><title>A &amp; B</title><style>a > b { color: red; }</style></head>\
+            <body><!-- note --><p class=\"x\" title=\"&quot;q&quot; &amp; a\">a &lt; b<br>c&nbsp;d</p>\
+            <input disabled=\"\"><pre>\n\nline</pre><script>if (a < b && c) {}</script></body></html>";
+
+        let dom = HTMLParser::new(String::from(html)).parse();
+        assert_eq!(html, dom.get_outer_html(dom.get_document()));
+    }
+
+    #[test]
+    fn test_get_outer_html_round_trip() {
+        let html = "<p>one<p>two<ul><li>a<li>b</ul><img src=a.png alt='x \"y\"'><textarea>\n&lt;b&gt;</textarea>";
+        let dom = HTMLParser::new(String::from(html)).parse();
+        let serialized = dom.get_outer_html(dom.get_document());
+        assert_eq!(
+            "<html><head></head><body><p>one</p><p>two</p><ul><li>a</li><li>b</li></ul><img src=\"a.png\" alt=\"x &quot;y&quot;\"><textarea>&lt;b&gt;</textarea></body></html>",
+            serialized
+        );
+
+        let reparsed = HTMLParser::new(serialized.clone()).parse();
+        assert_eq!(serialized, reparsed.get_outer_html(reparsed.get_document()));
+    }
+
+    #[test]
+    fn test_get_outer_html_round_trip_raw_text() {
+        let html = "<div><xmp>&lt;b&gt; <i></xmp><script>a < b</script></div>";
+        let dom = HTMLParser::new(String::from(html)).parse();
+        let serialized = dom.get_outer_html(dom.get_document());
+        assert_eq!("<html><head></head><body><div><xmp>&lt;b&gt; <i></xmp><script>a < b</script></div></body></html>", serialized);
+
+        let reparsed = HTMLParser::new(serialized.clone()).parse();
+        assert_eq!(serialized, reparsed.get_outer_html(reparsed.get_document()));
+    }
+
+    #[test]
+    fn test_get_inner_html() {
+        let mut dom = Dom::new();
+        let div = dom.create_element(String::from("div"), Vec::new());
+        let text = dom.create_text(String::from("a & b"));
+        let em = dom.create_element(String::from("em"), vec![(String::from("id"), String::from("x"))]);
+        let em_text = dom.create_text(String::from("c"));
+        dom.append_child(div, text).unwrap();
+        dom.append_child(div, em).unwrap();
+        dom.append_child(em, em_text).unwrap();
+
+        assert_eq!("a &amp; b<em id=\"x\">c</em>", dom.get_inner_html(div));
+        assert_eq!("<div>a &amp; b<em id=\"x\">c</em></div>", dom.get_outer_html(div));
+    }
+
+    #[test]
+    fn test_get_pretty_html() {
+        let html = "<!DOCTYPE html><html><head><title> Title </title></head><body>\n  <div id=main>\n    <p>Hello <em>world</em>!</p>\n  \
+            <p>Only text</p><br><pre>  keep\n  this</pre>\n  </div><!--end--></body></html>";
+        let dom = HTMLParser::new(String::from(html)).parse();
+
+        assert_eq!(
+            "<!DOCTYPE html>
+<html>
+  <head>
+    <title>Title</title>
+  </head>
+  <body>
+    <div id=\"main\">
+      <p>
+        Hello
+        <em>world</em>
+        !
+      </p>
+      <p>Only text</p>
+      <br>
+      <pre>  keep
+  this</pre>
+    </div>
+    <!--end-->
+  </body>
+</html>
+",
+            dom.get_pretty_html(dom.get_document())
+        );
+    }
+}
@@ -0,0 +1,23 @@
+// The output of the tokenizer. Runs of text are emitted as a single Character token.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token {
+    StartTag {
+        tag_name: String,
+        // Attributes in source order, with character references decoded; names are unique.
+        attributes: Vec<(String, String)>,
+        is_self_closing: bool
+    },
+    EndTag {
+        tag_name: String
+    },
+    Character(String),
+    Comment(String),
+    Doctype {
+        name: String,
+        public_id: Option<String>,
+        system_id: Option<String>,
+        // Set for a doctype that is too broken to tell whether the document is standards compliant.
+        force_quirks: bool
+    },
+    Eof
+}
@@ -0,0 +1,729 @@
+use std::collections::VecDeque;
+use crate::general_parser::GeneralParser;
+use crate::html_parser::RAW_TEXT_ELEMENTS;
+use crate::html_parser::character_reference::decode_character_references;
+use crate::html_parser::token::Token;
+use crate::parse_error::ParseError;
+use crate::source_span::SourceSpan;
+
+// Like raw text elements, except that character references are decoded.
+const RCDATA_ELEMENTS: [&str; 2] = ["textarea", "title"];
+
+// The states of the HTML5 tokenizer. Comments, doctypes and raw text are each read by a
+// single state, since nothing inside them needs a state of its own here.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum TokenizerState {
+    Data,
+    RawText,
+    RcData,
+    TagOpen,
+    EndTagOpen,
+    TagName,
+    BeforeAttributeName,
+    AttributeName,
+    AfterAttributeName,
+    BeforeAttributeValue,
+    AttributeValueQuoted(char),
+    AttributeValueUnquoted,
+    AfterAttributeValueQuoted,
+    SelfClosingStartTag,
+    MarkupDeclarationOpen,
+    Comment,
+    BogusComment,
+    Doctype
+}
+
+// Splits HTML into tokens without building a tree. Markup that ends before it is complete is
+// handled as HTML5 does: an unterminated tag is dropped, while an unterminated comment or doctype
+// is still emitted. Either way the error is kept, and tokens always end with Eof.
+pub struct Tokenizer {
+    general_parser: GeneralParser,
+    file_id: usize,
+    state: TokenizerState,
+    // Where the markup being read started, i.e. the position of its '<'.
+    token_start: usize,
+    // Text is collected here until the next token, so that a run of text becomes one token.
+    text: String,
+    text_start: usize,
+    tag_name: String,
+    is_end_tag: bool,
+    attributes: Vec<(String, String)>,
+    // The attribute whose name or value is being read.
+    attribute: Option<(String, String)>,
+    // The start tag whose raw text content is being read, e.g. "script".
+    raw_text_tag_name: String,
+    tokens: VecDeque<(Token, SourceSpan)>,
+    errors: Vec<ParseError>,
+    is_finished: bool
+}
+
+impl Tokenizer {
+    pub fn new(content: String) -> Self {
+        Self {
+            general_parser: GeneralParser::new(content),
+            file_id: 0,
+            state: TokenizerState::Data,
+            token_start: 0,
+            text: String::new(),
+            text_start: 0,
+            tag_name: String::new(),
+            is_end_tag: false,
+            attributes: Vec::new(),
+            attribute: None,
+            raw_text_tag_name: String::new(),
+            tokens: VecDeque::new(),
+            errors: Vec::new(),
+            is_finished: false
+        }
+    }
+
+    // Sets the file that the spans of the tokens refer to.
+    pub fn set_file_id(&mut self, file_id: usize) {
+        self.file_id = file_id;
+    }
+
+    // Returns the errors found in the markup so far, all of which were recovered from.
+    pub fn get_errors(&self) -> &[ParseError] {
+        return &self.errors;
+    }
+
+    // Like next(), but also returns where in the source the token was found.
+    pub fn next_token(&mut self) -> Option<(Token, SourceSpan)> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Some(token);
+            }
+
+            if self.is_finished {
+                return None;
+            }
+
+            self.step();
+        }
+    }
+
+    // Runs the state machine for one transition, queueing any tokens that are completed.
+    fn step(&mut self) {
+        match self.state {
+            TokenizerState::Data => {
+                let current_position = self.general_parser.get_position();
+
+                if self.general_parser.is_eof() {
+                    self.emit_text(current_position);
+                    self.token_start = current_position;
+                    self.emit(Token::Eof);
+                    self.is_finished = true;
+                } else if self.general_parser.peek_current_char() == '<' {
+                    self.token_start = current_position;
+                    self.general_parser.pop_current_char();
+                    self.state = TokenizerState::TagOpen;
+                } else {
+                    let text = decode_character_references(self.general_parser.consume_while(|char| char != '<'), false);
+                    self.push_text(text.as_str(), current_position);
+                }
+            },
+            TokenizerState::RawText | TokenizerState::RcData => {
+                let start_position = self.general_parser.get_position();
+                let text = self.consume_raw_text();
+
+                if self.state == TokenizerState::RcData {
+                    self.push_text(decode_character_references(text.as_str(), false).as_str(), start_position);
+                } else {
+                    self.push_text(text.as_str(), start_position);
+                }
+
+                self.state = TokenizerState::Data;
+            },
+            TokenizerState::TagOpen => {
+                if self.general_parser.is_eof() {
+                    self.push_text("<", self.token_start);
+                    self.state = TokenizerState::Data;
+                    return;
+                }
+
+                match self.general_parser.peek_current_char() {
+                    '!' => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::MarkupDeclarationOpen;
+                    },
+                    '/' => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::EndTagOpen;
+                    },
+                    // Processing instructions such as <?xml version="1.0"?> are not part of HTML.
+                    '?' => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::BogusComment;
+                    },
+                    char if char.is_ascii_alphabetic() => {
+                        self.start_tag(false);
+                    },
+                    // A '<' that does not start a tag, as in "a < b", is ordinary text.
+                    _ => {
+                        self.push_text("<", self.token_start);
+                        self.state = TokenizerState::Data;
+                    }
+                }
+            },
+            TokenizerState::EndTagOpen => {
+                match self.general_parser.try_peek_current_char() {
+                    // A "</" at the end of the input is text.
+                    Err(error) => {
+                        self.errors.push(error);
+                        self.push_text("</", self.token_start);
+                        self.state = TokenizerState::Data;
+                    },
+                    Ok(char) if char.is_ascii_alphabetic() => {
+                        self.start_tag(true);
+                    },
+                    // An end tag without a name, </>, is ignored.
+                    Ok('>') => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::Data;
+                    },
+                    // An end tag that does not start with a letter, such as </3>, is kept as a comment.
+                    _ => {
+                        self.state = TokenizerState::BogusComment;
+                    }
+                }
+            },
+            TokenizerState::TagName => {
+                let tag_name = self.general_parser.consume_while(|char| char != '/' && char != '>' && !char.is_whitespace());
+                // HTML names are ASCII case-insensitive, so they are normalized to lowercase.
+                self.tag_name.push_str(tag_name);
+                self.tag_name.make_ascii_lowercase();
+
+                match self.peek_tag_char() {
+                    Some('/') => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::SelfClosingStartTag;
+                    },
+                    Some('>') => self.emit_tag(),
+                    Some(_) => self.state = TokenizerState::BeforeAttributeName,
+                    None => {}
+                }
+            },
+            TokenizerState::BeforeAttributeName => {
+                self.general_parser.consume_whitespaces();
+
+                match self.peek_tag_char() {
+                    Some('/' | '>') => self.state = TokenizerState::AfterAttributeName,
+                    // An attribute name may start with '=', e.g. <div =a>, but cannot contain one after that.
+                    Some('=') => {
+                        self.general_parser.pop_current_char();
+                        self.attribute = Some((String::from("="), String::new()));
+                        self.state = TokenizerState::AttributeName;
+                    },
+                    Some(_) => {
+                        self.attribute = Some((String::new(), String::new()));
+                        self.state = TokenizerState::AttributeName;
+                    },
+                    None => {}
+                }
+            },
+            TokenizerState::AttributeName => {
+                let name = self.general_parser.consume_while(|char| {
+                    match char {
+                        '/' | '>' | '=' => false,
+                        c => !c.is_whitespace()
+                    }
+                });
+
+                if let Some((attribute_name, _)) = &mut self.attribute {
+                    attribute_name.push_str(name);
+                    attribute_name.make_ascii_lowercase();
+                }
+
+                match self.peek_tag_char() {
+                    Some('=') => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::BeforeAttributeValue;
+                    },
+                    Some(_) => self.state = TokenizerState::AfterAttributeName,
+                    None => {}
+                }
+            },
+            TokenizerState::AfterAttributeName => {
+                self.general_parser.consume_whitespaces();
+
+                match self.peek_tag_char() {
+                    // A stray slash inside a tag, as in <a / href='x'>, is ignored unless it ends the tag.
+                    Some('/') => {
+                        self.general_parser.pop_current_char();
+                        self.finish_attribute();
+                        self.state = TokenizerState::SelfClosingStartTag;
+                    },
+                    Some('=') => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::BeforeAttributeValue;
+                    },
+                    Some('>') => self.emit_tag(),
+                    // A boolean attribute such as <input disabled> has no value.
+                    Some(_) => {
+                        self.finish_attribute();
+                        self.attribute = Some((String::new(), String::new()));
+                        self.state = TokenizerState::AttributeName;
+                    },
+                    None => {}
+                }
+            },
+            TokenizerState::BeforeAttributeValue => {
+                self.general_parser.consume_whitespaces();
+
+                match self.peek_tag_char() {
+                    Some(open_quote @ ('"' | '\'')) => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::AttributeValueQuoted(open_quote);
+                    },
+                    Some('>') => self.emit_tag(),
+                    Some(_) => self.state = TokenizerState::AttributeValueUnquoted,
+                    None => {}
+                }
+            },
+            TokenizerState::AttributeValueQuoted(open_quote) => {
+                let value = decode_character_references(self.general_parser.consume_while(|char| char != open_quote), true);
+
+                if self.peek_tag_char().is_some() {
+                    self.general_parser.pop_current_char();
+                    self.set_attribute_value(value);
+                    self.state = TokenizerState::AfterAttributeValueQuoted;
+                }
+            },
+            TokenizerState::AttributeValueUnquoted => {
+                let value = decode_character_references(self.general_parser.consume_while(|char| char != '>' && !char.is_whitespace()), true);
+                self.set_attribute_value(value);
+
+                match self.peek_tag_char() {
+                    Some('>') => self.emit_tag(),
+                    Some(_) => self.state = TokenizerState::BeforeAttributeName,
+                    None => {}
+                }
+            },
+            TokenizerState::AfterAttributeValueQuoted => {
+                match self.peek_tag_char() {
+                    Some('/') => {
+                        self.general_parser.pop_current_char();
+                        self.state = TokenizerState::SelfClosingStartTag;
+                    },
+                    Some('>') => self.emit_tag(),
+                    // Also covers attributes without whitespace between them, as in <a x='1'y='2'>.
+                    Some(_) => self.state = TokenizerState::BeforeAttributeName,
+                    None => {}
+                }
+            },
+            TokenizerState::SelfClosingStartTag => {
+                match self.peek_tag_char() {
+                    Some('>') => self.emit_self_closing_tag(),
+                    Some(_) => self.state = TokenizerState::BeforeAttributeName,
+                    None => {}
+                }
+            },
+            TokenizerState::MarkupDeclarationOpen => {
+                if self.general_parser.consume_str("--") {
+                    self.state = TokenizerState::Comment;
+                } else if self.general_parser.consume_str_ignore_case("DOCTYPE") {
+                    self.state = TokenizerState::Doctype;
+                } else {
+                    self.state = TokenizerState::BogusComment;
+                }
+            },
+            TokenizerState::Comment => {
+                // <!--> and <!---> are complete, empty comments.
+                let comment = if self.general_parser.is_started_with(">") || self.general_parser.is_started_with("->") {
+                    String::new()
+                } else {
+                    String::from(self.general_parser.consume_until("-->"))
+                };
+
+                self.general_parser.consume_while(|char| char == '-');
+
+                // A comment that is not closed runs to the end of the input.
+                if let Err(error) = self.general_parser.expect_char('>') {
+                    self.errors.push(error);
+                }
+                self.emit(Token::Comment(comment));
+            },
+            TokenizerState::BogusComment => {
+                let comment = String::from(self.general_parser.consume_while(|char| char != '>'));
+                self.general_parser.consume_str(">");
+                self.emit(Token::Comment(comment));
+            },
+            TokenizerState::Doctype => {
+                let doctype = self.consume_doctype();
+                self.emit(doctype);
+            }
+        }
+    }
+
+    // Peeks at the next character inside a tag. A tag that is cut off by the end of the input
+    // is dropped, and None is returned after going back to the data state, which then emits Eof.
+    fn peek_tag_char(&mut self) -> Option<char> {
+        return match self.general_parser.try_peek_current_char() {
+            Ok(char) => Some(char),
+            Err(error) => {
+                self.errors.push(error);
+                // The text before the dropped tag ends where the tag started.
+                self.emit_text(self.token_start);
+                self.state = TokenizerState::Data;
+                None
+            }
+        };
+    }
+
+    // Queues a token, preceded by the text collected before it, and returns to the data state.
+    fn emit(&mut self, token: Token) {
+        self.emit_text(self.token_start);
+
+        let span = self.general_parser.get_source_span(self.file_id, self.token_start..self.general_parser.get_position());
+        self.tokens.push_back((token, span));
+        self.state = TokenizerState::Data;
+    }
+
+    fn push_text(&mut self, text: &str, start_position: usize) {
+        if self.text.is_empty() {
+            self.text_start = start_position;
+        }
+
+        self.text.push_str(text);
+    }
+
+    // Queues the collected text, which ends where the following markup starts.
+    fn emit_text(&mut self, end_position: usize) {
+        if !self.text.is_empty() {
+            let span = self.general_parser.get_source_span(self.file_id, self.text_start..end_position);
+            self.tokens.push_back((Token::Character(std::mem::take(&mut self.text)), span));
+        }
+    }
+
+    fn start_tag(&mut self, is_end_tag: bool) {
+        self.tag_name = String::new();
+        self.is_end_tag = is_end_tag;
+        self.attributes = Vec::new();
+        self.attribute = None;
+        self.state = TokenizerState::TagName;
+    }
+
+    // Sets the value of the current attribute, with its character references already decoded.
+    fn set_attribute_value(&mut self, value: String) {
+        if let Some((_, attribute_value)) = &mut self.attribute {
+            *attribute_value = value;
+        }
+
+        self.finish_attribute();
+    }
+
+    fn finish_attribute(&mut self) {
+        if let Some((name, value)) = self.attribute.take() {
+            // When an attribute is repeated, the first occurrence wins.
+            if !self.attributes.iter().any(|(existing_name, _)| *existing_name == name) {
+                self.attributes.push((name, value));
+            }
+        }
+    }
+
+    fn emit_self_closing_tag(&mut self) {
+        self.general_parser.pop_current_char();
+        self.finish_attribute();
+
+        let tag_name = std::mem::take(&mut self.tag_name);
+
+        if self.is_end_tag {
+            self.emit(Token::EndTag { tag_name });
+        } else {
+            // XML-style self-closing tags, such as <br/> or <div />, never have children.
+            let attributes = std::mem::take(&mut self.attributes);
+            self.emit(Token::StartTag { tag_name, attributes, is_self_closing: true });
+        }
+    }
+
+    fn emit_tag(&mut self) {
+        self.general_parser.pop_current_char();
+        self.finish_attribute();
+
+        let tag_name = std::mem::take(&mut self.tag_name);
+
+        if self.is_end_tag {
+            self.emit(Token::EndTag { tag_name });
+            return;
+        }
+
+        let is_raw_text = RAW_TEXT_ELEMENTS.contains(&tag_name.as_str());
+        let is_rcdata = RCDATA_ELEMENTS.contains(&tag_name.as_str());
+        self.raw_text_tag_name = tag_name.clone();
+
+        let attributes = std::mem::take(&mut self.attributes);
+        self.emit(Token::StartTag { tag_name, attributes, is_self_closing: false });
+
+        if is_raw_text {
+            self.state = TokenizerState::RawText;
+        } else if is_rcdata {
+            self.state = TokenizerState::RcData;
+        }
+    }
+
+    // Consumes the content of a raw text or RCDATA element, stopping before its end tag.
+    fn consume_raw_text(&mut self) -> String {
+        let end_tag = format!("</{}", self.raw_text_tag_name);
+        let mut text = String::new();
+
+        loop {
+            text.push_str(self.general_parser.consume_until("</"));
+
+            if self.general_parser.is_eof() || self.is_started_with_end_tag(end_tag.as_str()) {
+                break;
+            }
+
+            text.push(self.general_parser.pop_current_char());
+        }
+
+        return text;
+    }
+
+    fn is_started_with_end_tag(&self, end_tag: &str) -> bool {
+        return self.general_parser.is_started_with_ignore_case(end_tag)
+            && self.general_parser
+                .peek_nth(end_tag.chars().count())
+                .is_none_or(|char| char == '>' || char == '/' || char.is_whitespace());
+    }
+
+    fn consume_doctype(&mut self) -> Token {
+        self.general_parser.consume_whitespaces();
+
+        let name = self.general_parser
+            .consume_while(|char| char != '>' && !char.is_whitespace())
+            .to_ascii_lowercase();
+        self.general_parser.consume_whitespaces();
+
+        let mut public_id = None;
+        let mut system_id = None;
+
+        if self.general_parser.consume_str_ignore_case("PUBLIC") {
+            self.general_parser.consume_whitespaces();
+            public_id = self.consume_doctype_identifier();
+            self.general_parser.consume_whitespaces();
+            system_id = self.consume_doctype_identifier();
+        }
+        else if self.general_parser.consume_str_ignore_case("SYSTEM") {
+            self.general_parser.consume_whitespaces();
+            system_id = self.consume_doctype_identifier();
+        }
+
+        self.general_parser.consume_while(|char| char != '>');
+
+        // A doctype cut off by the end of the input puts the document in quirks mode.
+        let is_terminated = match self.general_parser.expect_char('>') {
+            Ok(()) => true,
+            Err(error) => {
+                self.errors.push(error);
+                false
+            }
+        };
+
+        return Token::Doctype {
+            force_quirks: name.is_empty() || !is_terminated,
+            name,
+            public_id,
+            system_id
+        };
+    }
+
+    fn consume_doctype_identifier(&mut self) -> Option<String> {
+        if !self.general_parser.is_started_with("\"") && !self.general_parser.is_started_with("'") {
+            return None;
+        }
+
+        let open_quote = self.general_parser.pop_current_char();
+        let identifier = String::from(self.general_parser.consume_while(|char| char != open_quote && char != '>'));
+
+        if self.general_parser.is_started_with(open_quote.to_string().as_str()) {
+            self.general_parser.pop_current_char();
+        }
+
+        return Some(identifier);
+    }
+}
+
+impl Iterator for Tokenizer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        return self.next_token().map(|(token, _)| token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::token::Token;
+    use crate::html_parser::tokenizer::Tokenizer;
+    use crate::parse_error::ParseErrorKind;
+
+    fn tokenize(html: &str) -> Vec<Token> {
+        return Tokenizer::new(String::from(html)).collect();
+    }
+
+    fn create_start_tag(tag_name: &str, attributes: Vec<(&str, &str)>, is_self_closing: bool) -> Token {
+        return Token::StartTag {
+            tag_name: String::from(tag_name),
+            attributes: attributes.iter().map(|(name, value)| (String::from(*name), String::from(*value))).collect(),
+            is_self_closing
+        };
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![
+                Token::Doctype { name: String::from("html"), public_id: None, system_id: None, force_quirks: false },
+                create_start_tag("p", vec![("class", "note")], false),
+                Token::Character(String::from("a < b & c")),
+                create_start_tag("br", Vec::new(), true),
+                Token::EndTag { tag_name: String::from("p") },
+                Token::Comment(String::from(" end ")),
+                Token::Eof
+            ],
+            tokenize("<!doctype html><p class=note>a < b &amp; c<br/></p><!-- end -->")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_attributes() {
+        assert_eq!(
+            vec![
+                create_start_tag("td", vec![
+                    ("colspan", "2"),
+                    ("data-x-y", "1"),
+                    ("xml:lang", "en"),
+                    ("_private.flag", ""),
+                    ("disabled", ""),
+                    ("=a", ""),
+                    ("title", "a.png/")
+                ], false),
+                create_start_tag("input", vec![("disabled", ""), ("value", "x")], true),
+                Token::Eof
+            ],
+            tokenize("<td colspan=2 data-x-y=\"1\" xml:lang = 'en' _private.flag disabled colspan=3 /=a title=a.png/><input disabled value='x'/>")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_lowercases_names() {
+        assert_eq!(
+            vec![
+                create_start_tag("div", vec![("class", "Note"), ("data-id", "X")], false),
+                create_start_tag("textarea", Vec::new(), false),
+                Token::Character(String::from("<B>")),
+                Token::EndTag { tag_name: String::from("textarea") },
+                Token::EndTag { tag_name: String::from("div") },
+                Token::Eof
+            ],
+            tokenize("<DIV CLASS='Note' Data-ID=X class=y><TextArea><B></TEXTAREA></Div>")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_bogus_comments() {
+        assert_eq!(
+            vec![
+                Token::Comment(String::from("xml version=\"1.0\"?")),
+                Token::Comment(String::from("3")),
+                Token::Comment(String::from("[CDATA[x]]")),
+                Token::Comment(String::new()),
+                Token::Character(String::from("a<")),
+                Token::Eof
+            ],
+            tokenize("<?xml version=\"1.0\"?></3></><![CDATA[x]]><!-->a<")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_raw_text() {
+        assert_eq!(
+            vec![
+                create_start_tag("script", Vec::new(), false),
+                Token::Character(String::from("if (a < b) { s = '</div>'; } &amp;")),
+                Token::EndTag { tag_name: String::from("script") },
+                create_start_tag("title", Vec::new(), false),
+                Token::Character(String::from("<b> & ")),
+                Token::Eof
+            ],
+            tokenize("<script>if (a < b) { s = '</div>'; } &amp;</SCRIPT><title><b> &amp; ")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_recovers_from_unterminated_markup() {
+        assert_eq!(vec![Token::Eof], tokenize("<div"));
+        assert_eq!(vec![Token::Eof], tokenize("<div class='a"));
+        assert_eq!(vec![Token::Eof], tokenize("<br/"));
+        assert_eq!(vec![Token::Comment(String::from("x")), Token::Eof], tokenize("<!--x"));
+        assert_eq!(vec![Token::Comment(String::from("x")), Token::Eof], tokenize("<?x"));
+        assert_eq!(vec![Token::Character(String::from("a</")), Token::Eof], tokenize("a</"));
+        assert_eq!(vec![Token::Character(String::from("a<")), Token::Eof], tokenize("a<"));
+        assert_eq!(vec![Token::Character(String::from("a")), Token::Eof], tokenize("a<div"));
+        assert_eq!(
+            vec![
+                create_start_tag("script", Vec::new(), false),
+                Token::Character(String::from("a")),
+                Token::Eof
+            ],
+            tokenize("<script>a</script")
+        );
+        assert_eq!(
+            vec![Token::Doctype { name: String::from("html"), public_id: None, system_id: None, force_quirks: true }, Token::Eof],
+            tokenize("<!DOCTYPE html")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_reports_unterminated_markup() {
+        let mut tokenizer = Tokenizer::new(String::from("<p>text<a href='x"));
+        assert_eq!(Some(create_start_tag("p", Vec::new(), false)), tokenizer.next());
+        assert!(tokenizer.get_errors().is_empty());
+
+        // The text is only known to be complete once the tag after it turns out to be cut off.
+        assert_eq!(Some(Token::Character(String::from("text"))), tokenizer.next());
+        assert_eq!(Some(Token::Eof), tokenizer.next());
+        assert_eq!(None, tokenizer.next());
+
+        let errors = tokenizer.get_errors();
+        assert_eq!(1, errors.len());
+        assert_eq!(ParseErrorKind::UnexpectedEof, errors[0].kind);
+        assert_eq!(17..17, errors[0].span);
+    }
+
+    #[test]
+    fn test_next_token_reports_spans() {
+        let mut tokenizer = Tokenizer::new(String::from("<p class=a>\n  two\n</p>"));
+        tokenizer.set_file_id(1);
+
+        let spans: Vec<String> = std::iter::from_fn(|| tokenizer.next_token())
+            .map(|(_, span)| format!("{}", span))
+            .collect();
+        assert_eq!(vec!["1:1:1-1:12", "1:1:12-3:1", "1:3:1-3:5", "1:3:5-3:5"], spans);
+
+        // Text before a tag that is cut off by the end of the input ends where the tag starts.
+        let mut tokenizer = Tokenizer::new(String::from("a</b>c<p"));
+        let spans: Vec<String> = std::iter::from_fn(|| tokenizer.next_token())
+            .map(|(_, span)| format!("{}", span))
+            .collect();
+        assert_eq!(vec!["0:1:1-1:2", "0:1:2-1:6", "0:1:6-1:7", "0:1:9-1:9"], spans);
+    }
+
+    #[test]
+    fn test_extract_links_without_building_a_tree() {
+        let html = "<a href='/one'>One</a><script>document.write('<a href=\"/fake\">')</script><A HREF=/two>Two</A>";
+
+        let links: Vec<String> = Tokenizer::new(String::from(html))
+            .filter_map(|token| {
+                match token {
+                    Token::StartTag { tag_name, attributes, .. } if tag_name == "a" => {
+                        attributes.into_iter().find(|(name, _)| name == "href").map(|(_, value)| value)
+                    },
+                    _ => None
+                }
+            })
+            .collect();
+
+        assert_eq!(vec!["/one", "/two"], links);
+    }
+}
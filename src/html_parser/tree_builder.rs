@@ -0,0 +1,615 @@
+use crate::html_parser::dom::Dom;
+use crate::html_parser::doctype_data::DoctypeData;
+use crate::html_parser::is_void_element;
+use crate::html_parser::node_id::NodeId;
+use crate::html_parser::node_type::NodeType;
+use crate::html_parser::quirks_mode::QuirksMode;
+use crate::html_parser::token::Token;
+use crate::source_span::SourceSpan;
+
+// Elements that belong in <head> when they appear before the body starts.
+const HEAD_ELEMENTS: [&str; 10] = [
+    "base", "basefont", "bgsound", "link", "meta", "noframes", "script", "style", "template", "title"
+];
+
+// Start tags that implicitly close an open <p>.
+const CLOSES_PARAGRAPH_ELEMENTS: [&str; 38] = [
+    "address", "article", "aside", "blockquote", "center", "details", "dialog", "dir", "div", "dl",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hgroup", "hr", "main", "menu", "nav", "ol", "p", "pre", "section", "summary", "table", "ul",
+    "li", "dd", "dt", "listing"
+];
+
+const HEADING_ELEMENTS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+// Elements that may be closed implicitly when their parent is closed.
+const IMPLIED_END_TAG_ELEMENTS: [&str; 10] = [
+    "dd", "dt", "li", "optgroup", "option", "p", "rb", "rp", "rt", "rtc"
+];
+
+// An end tag for these elements is only honoured when the element is in scope.
+const SCOPED_END_TAG_ELEMENTS: [&str; 44] = [
+    "address", "article", "aside", "blockquote", "button", "center", "details", "dialog", "dir", "div",
+    "dl", "fieldset", "figcaption", "figure", "footer", "form", "header", "hgroup", "listing", "main",
+    "menu", "nav", "ol", "pre", "section", "summary", "ul", "li", "dd", "dt", "p", "h1", "h2", "h3",
+    "h4", "h5", "h6", "table", "tbody", "thead", "tfoot", "tr", "td", "th"
+];
+
+// Elements that stop the search for an element "in scope".
+const SCOPE_BOUNDARY_ELEMENTS: [&str; 9] = [
+    "applet", "caption", "html", "table", "td", "th", "marquee", "object", "template"
+];
+
+// Elements with special parsing rules; an unmatched end tag never closes them implicitly.
+const SPECIAL_ELEMENTS: [&str; 79] = [
+    "address", "applet", "area", "article", "aside", "base", "basefont", "bgsound", "blockquote", "body",
+    "br", "button", "caption", "center", "col", "colgroup", "dd", "details", "dir", "div", "dl", "dt",
+    "embed", "fieldset", "figcaption", "figure", "footer", "form", "frame", "frameset", "h1", "h2", "h3",
+    "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "iframe", "img", "input", "li", "link",
+    "listing", "main", "marquee", "menu", "meta", "nav", "noembed", "noframes", "noscript", "object",
+    "ol", "p", "param", "plaintext", "pre", "script", "section", "select", "source", "style", "summary",
+    "table", "tbody", "td", "template", "textarea", "tfoot", "th", "thead", "title", "tr", "track", "ul"
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum InsertionMode {
+    BeforeHtml,
+    BeforeHead,
+    InHead,
+    AfterHead,
+    InBody
+}
+
+// Builds a Dom the way an HTML5 parser does, recovering from missing,
+// misnested and unmatched tags instead of rejecting the document.
+pub struct TreeBuilder {
+    dom: Dom,
+    // The document node at the bottom, followed by the elements that are still open.
+    open_elements: Vec<NodeId>,
+    insertion_mode: InsertionMode,
+    // Set right after <pre>, <listing> and <textarea>, whose first line feed is dropped.
+    ignores_next_line_feed: bool,
+    // The span of the token being processed, given to the nodes created for it.
+    source_span: Option<SourceSpan>
+}
+
+impl TreeBuilder {
+    pub fn new() -> Self {
+        let mut dom = Dom::new();
+        let document = dom.get_document();
+        // A document is in quirks mode unless its doctype says otherwise.
+        dom.quirks_mode = QuirksMode::Quirks;
+
+        Self {
+            dom,
+            open_elements: vec![document],
+            insertion_mode: InsertionMode::BeforeHtml,
+            ignores_next_line_feed: false,
+            source_span: None
+        }
+    }
+
+    pub fn process_token(&mut self, token: Token, source_span: Option<SourceSpan>) {
+        self.source_span = source_span;
+        let ignores_line_feed = std::mem::replace(&mut self.ignores_next_line_feed, false);
+
+        match token {
+            Token::StartTag { tag_name, attributes, is_self_closing } => {
+                // A line feed right after these start tags is only there for readability of the source.
+                self.ignores_next_line_feed = ["pre", "listing", "textarea"].contains(&tag_name.as_str());
+                self.insert_start_tag(tag_name, attributes, is_self_closing);
+            },
+            Token::EndTag { tag_name } => self.insert_end_tag(tag_name.as_str()),
+            Token::Character(text) => {
+                match text.strip_prefix('\n') {
+                    Some(text) if ignores_line_feed => self.insert_text(String::from(text)),
+                    _ => self.insert_text(text)
+                }
+            },
+            Token::Comment(comment) => self.insert_comment(comment),
+            Token::Doctype { name, public_id, system_id, force_quirks } => {
+                self.insert_doctype(DoctypeData::new(name, public_id, system_id), force_quirks);
+            },
+            Token::Eof => {}
+        }
+    }
+
+    pub fn insert_doctype(&mut self, doctype_data: DoctypeData, force_quirks: bool) {
+        // A doctype is only meaningful before anything else in the document.
+        if self.insertion_mode == InsertionMode::BeforeHtml {
+            self.dom.quirks_mode = QuirksMode::from_doctype(
+                doctype_data.name.as_str(),
+                doctype_data.public_id.as_ref(),
+                doctype_data.system_id.as_ref(),
+                force_quirks
+            );
+
+            let doctype = self.dom.create_node(NodeType::Doctype(doctype_data));
+            self.dom.get_node_mut(doctype).source_span = self.source_span;
+            self.dom.insert_node(self.open_elements[0], doctype, None);
+        }
+    }
+
+    pub fn insert_comment(&mut self, comment: String) {
+        let comment = self.dom.create_comment(comment);
+        self.dom.get_node_mut(comment).source_span = self.source_span;
+        self.dom.insert_node(self.get_current_node(), comment, None);
+    }
+
+    pub fn insert_text(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+
+        // Text inside an element of the head, such as <title>, stays there.
+        let is_in_head_element = self.insertion_mode == InsertionMode::InHead && self.get_current_tag_name() != Some("head");
+
+        if self.insertion_mode != InsertionMode::InBody && !is_in_head_element {
+            // Whitespace between <html>, <head> and <body> is not content.
+            if text.chars().all(|char| char.is_whitespace()) {
+                return;
+            }
+
+            self.ensure_body();
+        }
+
+        let current_node = self.get_current_node();
+
+        // Adjacent text is merged into a single text node.
+        if let Some(last_child) = self.dom.get_node(current_node).get_last_child() {
+            let last_child = self.dom.get_node_mut(last_child);
+
+            if let NodeType::Text(previous_text) = &mut last_child.node_type {
+                previous_text.push_str(text.as_str());
+
+                if let (Some(previous_span), Some(source_span)) = (&mut last_child.source_span, &self.source_span) {
+                    previous_span.extend_to(source_span);
+                }
+                return;
+            }
+        }
+
+        let text = self.dom.create_text(text);
+        self.dom.get_node_mut(text).source_span = self.source_span;
+        self.dom.insert_node(current_node, text, None);
+    }
+
+    pub fn insert_start_tag(&mut self, tag_name: String, attributes: Vec<(String, String)>, is_self_closing: bool) {
+        match tag_name.as_str() {
+            "html" => {
+                if self.insertion_mode == InsertionMode::BeforeHtml {
+                    self.push_element(tag_name, attributes, self.source_span);
+                    self.insertion_mode = InsertionMode::BeforeHead;
+                } else {
+                    merge_attributes(&mut self.dom, self.open_elements[1], attributes);
+                }
+                return;
+            },
+            "head" => {
+                self.ensure_html();
+
+                if self.insertion_mode == InsertionMode::BeforeHead {
+                    self.push_element(tag_name, attributes, self.source_span);
+                    self.insertion_mode = InsertionMode::InHead;
+                }
+                return;
+            },
+            "body" => {
+                if self.insertion_mode == InsertionMode::InBody {
+                    merge_attributes(&mut self.dom, self.open_elements[2], attributes);
+                } else {
+                    self.ensure_head_closed();
+                    self.push_element(tag_name, attributes, self.source_span);
+                    self.insertion_mode = InsertionMode::InBody;
+                }
+                return;
+            },
+            _ => {}
+        }
+
+        let is_head_element = HEAD_ELEMENTS.contains(&tag_name.as_str());
+
+        if is_head_element && matches!(self.insertion_mode, InsertionMode::BeforeHtml | InsertionMode::BeforeHead | InsertionMode::InHead) {
+            self.ensure_head();
+        } else {
+            self.ensure_body();
+            self.close_implied_elements(tag_name.as_str());
+        }
+
+        let is_void = is_void_element(tag_name.as_str());
+        self.push_element(tag_name, attributes, self.source_span);
+
+        if is_void || is_self_closing {
+            self.pop_current_node();
+        }
+    }
+
+    pub fn insert_end_tag(&mut self, tag_name: &str) {
+        match tag_name {
+            "head" => {
+                if self.insertion_mode == InsertionMode::InHead {
+                    // Elements left open inside <head>, such as an unclosed <template>, are closed with it.
+                    self.close_element("head");
+                    self.insertion_mode = InsertionMode::AfterHead;
+                }
+            },
+            // <html> and <body> stay open until the end of the document, so that
+            // content after a premature </body> still ends up in the body.
+            "html" | "body" => {},
+            "p" => {
+                self.ensure_body();
+
+                if !self.is_in_scope("p", &[]) {
+                    self.push_element(String::from("p"), Vec::new(), self.source_span);
+                }
+                self.close_element("p");
+            },
+            _ if self.insertion_mode != InsertionMode::InBody => {
+                // Closes an element such as <title> that was opened in the head.
+                if self.get_current_tag_name() == Some(tag_name) {
+                    self.extend_to_end_tag(self.get_current_node());
+                    self.pop_current_node();
+                }
+            },
+            "li" => {
+                if self.is_in_scope("li", &["ol", "ul"]) {
+                    self.close_element("li");
+                }
+            },
+            _ if HEADING_ELEMENTS.contains(&tag_name) => {
+                // Any heading end tag closes the open heading, e.g. <h1>Title</h2>.
+                if let Some(heading) = HEADING_ELEMENTS.iter().find(|heading| self.is_in_scope(heading, &[])) {
+                    self.close_element(heading);
+                }
+            },
+            _ if SCOPED_END_TAG_ELEMENTS.contains(&tag_name) => {
+                if self.is_in_scope(tag_name, &[]) {
+                    self.close_element(tag_name);
+                }
+            },
+            _ => self.close_any_other_element(tag_name)
+        }
+    }
+
+    // Closes every open element and returns the finished Dom.
+    pub fn finish(mut self) -> Dom {
+        self.ensure_body();
+        return self.dom;
+    }
+
+    fn get_current_node(&self) -> NodeId {
+        return *self.open_elements.last().unwrap();
+    }
+
+    fn get_current_tag_name(&self) -> Option<&str> {
+        return self.get_tag_name(self.get_current_node());
+    }
+
+    fn get_tag_name(&self, node_id: NodeId) -> Option<&str> {
+        return self.dom.get_element_data(node_id).map(|element_data| element_data.tag_name.as_str());
+    }
+
+    // Elements are attached to the tree as soon as they are opened. Elements implied by the
+    // parser, such as a missing <body>, have no source span.
+    fn push_element(&mut self, tag_name: String, attributes: Vec<(String, String)>, source_span: Option<SourceSpan>) {
+        let element = self.dom.create_element(tag_name, attributes);
+        self.dom.get_node_mut(element).source_span = source_span;
+        self.dom.insert_node(self.get_current_node(), element, None);
+        self.open_elements.push(element);
+    }
+
+    fn pop_current_node(&mut self) {
+        // The document node itself is never popped.
+        if self.open_elements.len() > 1 {
+            self.open_elements.pop();
+        }
+    }
+
+    fn pop_until(&mut self, tag_name: &str) {
+        while self.open_elements.len() > 1 {
+            let is_target = self.get_current_tag_name() == Some(tag_name);
+            self.pop_current_node();
+
+            if is_target {
+                break;
+            }
+        }
+    }
+
+    // Closes an element for its end tag, so that the element's span covers the end tag too.
+    fn close_element(&mut self, tag_name: &str) {
+        if let Some(node_id) = self.open_elements.iter().rev().find(|node_id| self.get_tag_name(**node_id) == Some(tag_name)) {
+            self.extend_to_end_tag(*node_id);
+        }
+
+        self.pop_until(tag_name);
+    }
+
+    fn extend_to_end_tag(&mut self, node_id: NodeId) {
+        if let (Some(element_span), Some(end_tag_span)) = (&mut self.dom.get_node_mut(node_id).source_span, &self.source_span) {
+            element_span.extend_to(end_tag_span);
+        }
+    }
+
+    fn is_in_scope(&self, tag_name: &str, extra_boundaries: &[&str]) -> bool {
+        for node_id in self.open_elements.iter().rev() {
+            let current_tag_name = match self.get_tag_name(*node_id) {
+                Some(current_tag_name) => current_tag_name,
+                None => return false
+            };
+
+            if current_tag_name == tag_name {
+                return true;
+            }
+
+            if SCOPE_BOUNDARY_ELEMENTS.contains(&current_tag_name) || extra_boundaries.contains(&current_tag_name) {
+                return false;
+            }
+        }
+
+        return false;
+    }
+
+    fn ensure_html(&mut self) {
+        if self.insertion_mode == InsertionMode::BeforeHtml {
+            self.push_element(String::from("html"), Vec::new(), None);
+            self.insertion_mode = InsertionMode::BeforeHead;
+        }
+    }
+
+    fn ensure_head(&mut self) {
+        self.ensure_html();
+
+        if self.insertion_mode == InsertionMode::BeforeHead {
+            self.push_element(String::from("head"), Vec::new(), None);
+            self.insertion_mode = InsertionMode::InHead;
+        }
+    }
+
+    fn ensure_head_closed(&mut self) {
+        self.ensure_head();
+
+        if self.insertion_mode == InsertionMode::InHead {
+            // Elements left open inside <head>, such as an unclosed <title>, are closed with it.
+            self.pop_until("head");
+            self.insertion_mode = InsertionMode::AfterHead;
+        }
+    }
+
+    fn ensure_body(&mut self) {
+        self.ensure_head_closed();
+
+        if self.insertion_mode == InsertionMode::AfterHead {
+            self.push_element(String::from("body"), Vec::new(), None);
+            self.insertion_mode = InsertionMode::InBody;
+        }
+    }
+
+    // Closes the elements that a new start tag ends implicitly, e.g. <p>one<p>two.
+    fn close_implied_elements(&mut self, tag_name: &str) {
+        if CLOSES_PARAGRAPH_ELEMENTS.contains(&tag_name) && self.is_in_scope("p", &["button"]) {
+            self.pop_until("p");
+        }
+
+        match tag_name {
+            "li" => self.close_list_item(&["li"], &["ol", "ul"]),
+            "dd" | "dt" => self.close_list_item(&["dd", "dt"], &["dl"]),
+            "option" => {
+                if self.get_current_tag_name() == Some("option") {
+                    self.pop_current_node();
+                }
+            },
+            "tr" => self.close_table_item(&["tr"], &["table", "tbody", "thead", "tfoot"]),
+            "td" | "th" => self.close_table_item(&["td", "th"], &["tr", "table"]),
+            _ if HEADING_ELEMENTS.contains(&tag_name) => {
+                if let Some(current_tag_name) = self.get_current_tag_name() {
+                    if HEADING_ELEMENTS.contains(&current_tag_name) {
+                        self.pop_current_node();
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn close_list_item(&mut self, item_tag_names: &[&str], list_tag_names: &[&str]) {
+        for index in (1..self.open_elements.len()).rev() {
+            let current_tag_name = self.get_tag_name(self.open_elements[index]).unwrap_or("");
+
+            if item_tag_names.contains(&current_tag_name) {
+                let current_tag_name = current_tag_name.to_string();
+                self.pop_until(current_tag_name.as_str());
+                return;
+            }
+
+            if list_tag_names.contains(&current_tag_name)
+                || (SPECIAL_ELEMENTS.contains(&current_tag_name) && !["address", "div", "p"].contains(&current_tag_name)) {
+                return;
+            }
+        }
+    }
+
+    fn close_table_item(&mut self, item_tag_names: &[&str], container_tag_names: &[&str]) {
+        for index in (1..self.open_elements.len()).rev() {
+            let current_tag_name = self.get_tag_name(self.open_elements[index]).unwrap_or("");
+
+            if container_tag_names.contains(&current_tag_name) {
+                return;
+            }
+
+            if item_tag_names.contains(&current_tag_name) {
+                while self.open_elements.len() > index {
+                    self.pop_current_node();
+                }
+                return;
+            }
+        }
+    }
+
+    // An end tag for an ordinary element closes it together with anything opened
+    // inside it, unless a special element like <div> is in the way.
+    fn close_any_other_element(&mut self, tag_name: &str) {
+        for index in (1..self.open_elements.len()).rev() {
+            let current_tag_name = self.get_tag_name(self.open_elements[index]).unwrap_or("");
+
+            if current_tag_name == tag_name {
+                self.extend_to_end_tag(self.open_elements[index]);
+
+                while self.open_elements.len() > index {
+                    self.pop_current_node();
+                }
+                return;
+            }
+
+            if SPECIAL_ELEMENTS.contains(&current_tag_name) && !IMPLIED_END_TAG_ELEMENTS.contains(&current_tag_name) {
+                return;
+            }
+        }
+    }
+}
+
+// A repeated <html> or <body> tag adds its attributes to the existing element.
+fn merge_attributes(dom: &mut Dom, node_id: NodeId, attributes: Vec<(String, String)>) {
+    if let NodeType::Element(element_data) = &mut dom.get_node_mut(node_id).node_type {
+        for (name, value) in attributes {
+            if element_data.get_attribute(name.as_str()).is_none() {
+                element_data.set_attribute(name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html_parser::dom::Dom;
+    use crate::html_parser::node_id::NodeId;
+    use crate::html_parser::node_type::NodeType;
+    use crate::html_parser::tree_builder::TreeBuilder;
+
+    fn get_tag_names(dom: &Dom, node_id: NodeId) -> Vec<String> {
+        return dom.children(node_id).map(|child| {
+            match &dom.get_node(child).node_type {
+                NodeType::Element(element_data) => element_data.tag_name.clone(),
+                NodeType::Text(text) => format!("#{}", text),
+                _ => String::from("?")
+            }
+        }).collect();
+    }
+
+    fn get_child(dom: &Dom, node_id: NodeId, index: usize) -> NodeId {
+        return dom.children(node_id).nth(index).unwrap();
+    }
+
+    fn get_body(dom: &Dom) -> NodeId {
+        let html = dom.get_node(dom.get_document()).get_last_child().unwrap();
+        return get_child(dom, html, 1);
+    }
+
+    #[test]
+    fn test_inserts_html_head_and_body() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("title"), Vec::new(), false);
+        tree_builder.insert_text(String::from("Title"));
+        tree_builder.insert_end_tag("title");
+        tree_builder.insert_text(String::from("Hello"));
+
+        let dom = tree_builder.finish();
+        assert_eq!(vec!["html"], get_tag_names(&dom, dom.get_document()));
+
+        let html = get_child(&dom, dom.get_document(), 0);
+        assert_eq!(vec!["head", "body"], get_tag_names(&dom, html));
+        assert_eq!(vec!["title"], get_tag_names(&dom, get_child(&dom, html, 0)));
+        assert_eq!(vec!["#Hello"], get_tag_names(&dom, get_child(&dom, html, 1)));
+    }
+
+    #[test]
+    fn test_head_end_tag_closes_open_head_elements() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("head"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("template"), Vec::new(), false);
+        tree_builder.insert_text(String::from("x"));
+        tree_builder.insert_end_tag("head");
+        tree_builder.insert_start_tag(String::from("body"), Vec::new(), false);
+        tree_builder.insert_text(String::from("y"));
+
+        let dom = tree_builder.finish();
+        let html = get_child(&dom, dom.get_document(), 0);
+        assert_eq!(vec!["head", "body"], get_tag_names(&dom, html));
+        assert_eq!(vec!["template"], get_tag_names(&dom, get_child(&dom, html, 0)));
+        assert_eq!(vec!["#x"], get_tag_names(&dom, get_child(&dom, get_child(&dom, html, 0), 0)));
+        assert_eq!(vec!["#y"], get_tag_names(&dom, get_body(&dom)));
+    }
+
+    #[test]
+    fn test_closes_implied_paragraphs_and_list_items() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("p"), Vec::new(), false);
+        tree_builder.insert_text(String::from("one"));
+        tree_builder.insert_start_tag(String::from("p"), Vec::new(), false);
+        tree_builder.insert_text(String::from("two"));
+        tree_builder.insert_start_tag(String::from("ul"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("li"), Vec::new(), false);
+        tree_builder.insert_text(String::from("a"));
+        tree_builder.insert_start_tag(String::from("li"), Vec::new(), false);
+        tree_builder.insert_text(String::from("b"));
+        tree_builder.insert_end_tag("ul");
+
+        let dom = tree_builder.finish();
+        let body = get_body(&dom);
+        assert_eq!(vec!["p", "p", "ul"], get_tag_names(&dom, body));
+
+        let list = get_child(&dom, body, 2);
+        assert_eq!(vec!["li", "li"], get_tag_names(&dom, list));
+        assert_eq!(vec!["#b"], get_tag_names(&dom, get_child(&dom, list, 1)));
+    }
+
+    #[test]
+    fn test_closes_implied_table_cells() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("table"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("tr"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("td"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("td"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("tr"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("th"), Vec::new(), false);
+
+        let dom = tree_builder.finish();
+        let table = get_child(&dom, get_body(&dom), 0);
+        assert_eq!(vec!["tr", "tr"], get_tag_names(&dom, table));
+        assert_eq!(vec!["td", "td"], get_tag_names(&dom, get_child(&dom, table, 0)));
+        assert_eq!(vec!["th"], get_tag_names(&dom, get_child(&dom, table, 1)));
+    }
+
+    #[test]
+    fn test_ignores_unmatched_end_tags() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("div"), Vec::new(), false);
+        tree_builder.insert_end_tag("span");
+        tree_builder.insert_end_tag("section");
+        tree_builder.insert_start_tag(String::from("em"), Vec::new(), false);
+        tree_builder.insert_text(String::from("a"));
+        tree_builder.insert_end_tag("div");
+        tree_builder.insert_end_tag("div");
+        tree_builder.insert_text(String::from("b"));
+
+        let dom = tree_builder.finish();
+        let body = get_body(&dom);
+        assert_eq!(vec!["div", "#b"], get_tag_names(&dom, body));
+        assert_eq!(vec!["em"], get_tag_names(&dom, get_child(&dom, body, 0)));
+    }
+
+    #[test]
+    fn test_end_tag_closes_misnested_inline_elements() {
+        let mut tree_builder = TreeBuilder::new();
+        tree_builder.insert_start_tag(String::from("b"), Vec::new(), false);
+        tree_builder.insert_start_tag(String::from("i"), Vec::new(), false);
+        tree_builder.insert_end_tag("b");
+        tree_builder.insert_text(String::from("c"));
+        tree_builder.insert_end_tag("p");
+
+        let dom = tree_builder.finish();
+        let body = get_body(&dom);
+        assert_eq!(vec!["b", "#c", "p"], get_tag_names(&dom, body));
+        assert_eq!(vec!["i"], get_tag_names(&dom, get_child(&dom, body, 0)));
+    }
+}
@@ -27,4 +27,42 @@ pub fn build_layout_tree<'a>(styled_node: &'a StyledNode) -> LayoutBox<'a> {
     }
 
     return root_layout_box;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css_parser::CSSParser;
+    use crate::html_parser::HTMLParser;
+    use crate::layout_tree_builder::box_type::BoxType;
+    use crate::layout_tree_builder::build_layout_tree;
+    use crate::layout_tree_builder::layout_box::LayoutBox;
+    use crate::style_tree_builder::create_styled_node;
+
+    fn collect_texts(layout_box: &LayoutBox, texts: &mut Vec<String>) {
+        if let Some(text) = layout_box.get_text() {
+            texts.push(String::from(text));
+        }
+
+        for child in &layout_box.children {
+            collect_texts(child, texts);
+        }
+    }
+
+    #[test]
+    fn test_build_layout_tree_with_processed_white_space() {
+        let dom = HTMLParser::new(String::from("<div>\n  <p> Hello   \n world </p>\n  <pre>  a\n   b</pre>\n</div>")).parse();
+        let stylesheet = CSSParser::new(String::from("html, body, div, p, pre { display: block; } head { display: none; }")).parse();
+        let styled_node = create_styled_node(&dom, dom.get_document(), &vec![stylesheet]);
+        let layout_box = build_layout_tree(&styled_node);
+
+        let mut texts = Vec::new();
+        collect_texts(&layout_box, &mut texts);
+        assert_eq!(vec!["Hello world", "  a\n   b"], texts);
+
+        // The white space between the blocks is removed, so it generates no anonymous boxes.
+        let body_box = &layout_box.children[0].children[0];
+        let div_box = &body_box.children[0];
+        assert_eq!(2, div_box.children.len());
+        assert!(div_box.children.iter().all(|child| matches!(child.box_type, BoxType::Block)));
+    }
+}
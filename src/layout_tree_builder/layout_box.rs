@@ -2,12 +2,14 @@ use crate::css_parser::declaration_value::DeclarationValue;
 use crate::css_parser::declaration_value::DeclarationValue::{Keyword, Length};
 use crate::css_parser::length_unit::LengthUnit;
 use crate::css_parser::length_unit::LengthUnit::Px;
+use crate::html_parser::dom::Dom;
 use crate::layout_tree_builder::box_type::BoxType;
 use crate::layout_tree_builder::dimensions::Dimensions;
+use crate::source_span::SourceSpan;
 use crate::style_tree_builder::styled_node::StyledNode;
 
 pub struct LayoutBox<'a> {
-    pub styled_node: Option<&'a StyledNode<'a>>,
+    pub styled_node: Option<&'a StyledNode>,
     pub dimensions: Dimensions,
     pub box_type: BoxType,
     pub children: Vec<LayoutBox<'a>>
@@ -23,6 +25,24 @@ impl<'a> LayoutBox<'a> {
         }
     }
 
+    // The markup the box was generated from. Anonymous boxes and elements implied by the parser have none.
+    pub fn get_markup_span(&self, dom: &Dom) -> Option<SourceSpan> {
+        return dom.get_node(self.styled_node?.node_id).get_source_span();
+    }
+
+    // The CSS rules that styled the box, from the lowest to the highest precedence.
+    pub fn get_rule_spans(&self) -> &[SourceSpan] {
+        return match self.styled_node {
+            Some(styled_node) => styled_node.matched_rule_spans.as_slice(),
+            None => &[]
+        };
+    }
+
+    // The text of a box generated for a text node, with its white space processed.
+    pub fn get_text(&self) -> Option<&str> {
+        return self.styled_node?.text.as_deref();
+    }
+
     pub fn get_inline_box(&mut self) -> &mut LayoutBox<'a> {
         match self.box_type {
             BoxType::Inline | BoxType::Anonymous => self,
@@ -231,21 +251,21 @@ impl<'a> LayoutBox<'a> {
 mod tests {
     use std::collections::HashMap;
     use crate::css_parser::declaration_value::DeclarationValue::{Keyword, Length};
+    use crate::css_parser::CSSParser;
     use crate::css_parser::length_unit::LengthUnit;
-    use crate::html_parser::node::Node;
-    use crate::html_parser::node_type::NodeType;
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
     use crate::layout_tree_builder::box_type::BoxType;
+    use crate::layout_tree_builder::build_layout_tree;
     use crate::layout_tree_builder::layout_box::LayoutBox;
+    use crate::style_tree_builder::create_styled_node;
     use crate::style_tree_builder::styled_node::StyledNode;
 
     #[test]
     fn test_calculate_width() {
         let mut layout_box = LayoutBox::new(BoxType::Block);
         let styled_node = StyledNode {
-            dom_node: &Node {
-                children: Vec::new(),
-                node_type: NodeType::Text("abc".to_string())
-            },
+            node_id: Dom::new().get_document(),
             css_properties: HashMap::from([
                 ("width".to_string(), Length(7.5, LengthUnit::Px)),
                 ("margin-left".to_string(), Keyword("auto".to_string())),
@@ -255,6 +275,8 @@ mod tests {
                 ("padding-left".to_string(), Length(7.8, LengthUnit::Px)),
                 ("padding-right".to_string(), Length(8.9, LengthUnit::Px)),
             ]),
+            matched_rule_spans: Vec::new(),
+            text: None,
             children: vec![],
         };
         layout_box.styled_node = Some(&styled_node);
@@ -264,4 +286,29 @@ mod tests {
             layout_box.calculate_width_from_css()
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_source_spans() {
+        let dom = HTMLParser::new(String::from("<div>\n<p class=note>text</p>\n</div>")).parse();
+        let mut css_parser = CSSParser::new(String::from("* { display: block; }\n.note { margin-top: 4px; }\np { color: #000000; }"));
+        css_parser.set_source_location(1, 1, 1);
+        let stylesheets = vec![css_parser.parse()];
+
+        let styled_node = create_styled_node(&dom, dom.get_document(), &stylesheets);
+        let layout_box = build_layout_tree(&styled_node);
+
+        // The document, <html> and <body> have no markup of their own.
+        let body_box = &layout_box.children[0].children[1];
+        assert_eq!(None, body_box.get_markup_span(&dom));
+
+        // The line feed before <p> is collapsed away, so it generates no box.
+        let paragraph_box = &body_box.children[0].children[0];
+        assert_eq!("0:2:1-2:23", format!("{}", paragraph_box.get_markup_span(&dom).unwrap()));
+
+        let rule_spans: Vec<String> = paragraph_box.get_rule_spans().iter().map(|span| format!("{}", span)).collect();
+        assert_eq!(vec!["1:1:1-1:22", "1:3:1-3:22", "1:2:1-2:27"], rule_spans);
+
+        assert!(LayoutBox::new(BoxType::Anonymous).get_rule_spans().is_empty());
+        assert_eq!(None, LayoutBox::new(BoxType::Anonymous).get_markup_span(&dom));
+    }
+}
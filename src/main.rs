@@ -1,7 +1,12 @@
 mod general_parser;
+mod parse_error;
+mod source_span;
+mod encoding;
 mod html_parser;
 mod css_parser;
 mod style_tree_builder;
+mod white_space_processor;
+mod document_loader;
 mod layout_tree_builder;
 mod render;
 
@@ -0,0 +1,65 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedEof,
+    UnexpectedCharacter(char),
+    UnexpectedToken(String),
+    ExpectedCharacter { expected: char, found: char },
+    ExpectedString(String),
+    ExpectedIdentifier,
+    InvalidLengthUnit(String),
+    InvalidColor(String),
+    UnexpectedAtRule(String)
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            ParseErrorKind::UnexpectedCharacter(found) => write!(f, "unexpected character '{}'", found),
+            ParseErrorKind::UnexpectedToken(found) => write!(f, "unexpected '{}'", found),
+            ParseErrorKind::ExpectedCharacter { expected, found } => write!(f, "expected '{}' but found '{}'", expected, found),
+            ParseErrorKind::ExpectedString(expected) => write!(f, "expected '{}'", expected),
+            ParseErrorKind::ExpectedIdentifier => write!(f, "expected an identifier"),
+            ParseErrorKind::InvalidLengthUnit(unit) => write!(f, "unsupported length unit '{}'", unit),
+            ParseErrorKind::InvalidColor(color) => write!(f, "invalid color '#{}'", color),
+            ParseErrorKind::UnexpectedAtRule(name) => write!(f, "unexpected at-rule '@{}'", name)
+        }
+    }
+}
+
+// A parse failure, located by its 1-based line and column and by its byte span in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub span: Range<usize>
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at line {}, column {}", self.kind, self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_error::{ParseError, ParseErrorKind};
+
+    #[test]
+    fn test_display() {
+        let error = ParseError {
+            kind: ParseErrorKind::ExpectedCharacter { expected: ';', found: '}' },
+            line: 3,
+            column: 14,
+            span: 40..41
+        };
+
+        assert_eq!("expected ';' but found '}' at line 3, column 14", format!("{}", error));
+    }
+}
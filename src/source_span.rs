@@ -0,0 +1,55 @@
+use std::fmt::{Display, Formatter};
+
+// Where a node, rule, selector or declaration came from. Lines and columns are 1-based, columns
+// count characters, and the end is the position just after the last character.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceSpan {
+    // Identifies the file among those loaded together, e.g. an index into Document.source_files.
+    pub file_id: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize
+}
+
+impl SourceSpan {
+    // Moves a span measured from the start of an embedded source, such as the text of a <style>
+    // element, to where that source starts in its file.
+    pub fn offset_by(&self, line: usize, column: usize) -> SourceSpan {
+        let offset_column = |span_line: usize, span_column: usize| {
+            if span_line == 1 { span_column + column - 1 } else { span_column }
+        };
+
+        return SourceSpan {
+            file_id: self.file_id,
+            start_line: self.start_line + line - 1,
+            start_column: offset_column(self.start_line, self.start_column),
+            end_line: self.end_line + line - 1,
+            end_column: offset_column(self.end_line, self.end_column)
+        };
+    }
+
+    // Extends the span to the end of a later one, e.g. from a start tag to its end tag.
+    pub fn extend_to(&mut self, other: &SourceSpan) {
+        self.end_line = other.end_line;
+        self.end_column = other.end_column;
+    }
+}
+
+impl Display for SourceSpan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}-{}:{}", self.file_id, self.start_line, self.start_column, self.end_line, self.end_column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source_span::SourceSpan;
+
+    #[test]
+    fn test_offset_by() {
+        let span = SourceSpan { file_id: 2, start_line: 1, start_column: 3, end_line: 2, end_column: 5 };
+        assert_eq!("2:4:12-5:5", format!("{}", span.offset_by(4, 10)));
+        assert_eq!(span, span.offset_by(1, 1));
+    }
+}
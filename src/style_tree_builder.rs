@@ -1,54 +1,102 @@
 use std::collections::{HashMap, HashSet};
+use log::warn;
+use crate::css_parser::CSSParser;
+use crate::css_parser::combinator::Combinator;
+use crate::css_parser::declaration::Declaration;
 use crate::css_parser::declaration_value::DeclarationValue;
 use crate::css_parser::rule::Rule;
 use crate::css_parser::selector::Selector;
 use crate::css_parser::stylesheet::Stylesheet;
 use crate::html_parser::element_data::ElementData;
+use crate::html_parser::dom::Dom;
 use crate::html_parser::node::Node;
+use crate::html_parser::node_id::NodeId;
 use crate::html_parser::node_type::NodeType;
+use crate::html_parser::quirks_mode::QuirksMode;
 use crate::style_tree_builder::styled_node::StyledNode;
+use crate::white_space_processor::process_white_space;
 
 pub mod styled_node;
 
-fn check_if_selector_and_element_match(selector: &Selector, element: &ElementData) -> bool {
+// Matches from right to left: the last compound selector against the element, then the ones
+// before it against its parent or ancestors, as their combinators require.
+pub(crate) fn check_if_selector_and_element_match(selector: &Selector, dom: &Dom, node_id: NodeId) -> bool {
+    let element = match dom.get_element_data(node_id) {
+        Some(element) => element,
+        None => return false
+    };
+
+    if !check_if_compound_selector_and_element_match(selector, element, dom.get_quirks_mode()) {
+        return false;
+    }
+
+    return match &selector.combinator {
+        Some((Combinator::Child, previous_selector)) => {
+            dom.get_node(node_id).get_parent().is_some_and(|parent| check_if_selector_and_element_match(previous_selector, dom, parent))
+        },
+        Some((Combinator::Descendant, previous_selector)) => {
+            dom.ancestors(node_id).any(|ancestor| check_if_selector_and_element_match(previous_selector, dom, ancestor))
+        },
+        // Sibling combinators skip text and comments, only elements count.
+        Some((Combinator::NextSibling, previous_selector)) => {
+            dom.previous_siblings(node_id)
+                .find(|sibling| dom.get_element_data(*sibling).is_some())
+                .is_some_and(|sibling| check_if_selector_and_element_match(previous_selector, dom, sibling))
+        },
+        Some((Combinator::SubsequentSibling, previous_selector)) => {
+            dom.previous_siblings(node_id).any(|sibling| check_if_selector_and_element_match(previous_selector, dom, sibling))
+        },
+        None => true
+    };
+}
+
+fn check_if_compound_selector_and_element_match(selector: &Selector, element: &ElementData, quirks_mode: QuirksMode) -> bool {
     if !check_if_tags_matched(&selector.tag_name, &element.tag_name) {
         return false;
     }
 
-    if !check_if_ids_matched(&selector.id, &element.get_id()) {
+    // Ids and classes are case-sensitive, except in quirks mode.
+    let is_case_sensitive = quirks_mode != QuirksMode::Quirks;
+
+    if !check_if_ids_matched(&selector.id, &element.get_id(), is_case_sensitive) {
         return false;
     }
 
-    if !check_if_classes_matched(&selector.classes, element.get_classes()) {
+    if !check_if_classes_matched(&selector.classes, element.get_classes(), is_case_sensitive) {
         return false;
     }
 
     return true;
 }
 
-fn check_if_tags_matched(tag_in_selector: &Option<String>, tag_in_element: &String) -> bool {
+fn check_if_tags_matched(tag_in_selector: &Option<String>, tag_in_element: &str) -> bool {
     return match tag_in_selector {
-        // When a selector asks for a tag specifically, the element must have it.
+        // When a selector asks for a tag specifically, the element must have it. HTML tag names ignore ASCII case.
         Some(tag_in_selector) => {
-            return tag_in_selector == tag_in_element
+            return tag_in_selector.eq_ignore_ascii_case(tag_in_element)
         },
         // When a selector doesn't ask for a tag, keep checking other conditions.
         _ => true
     };
 }
 
-fn check_if_ids_matched(id_in_selector: &Option<String>, id_in_element: &Option<&String>) -> bool {
+fn check_if_ids_matched(id_in_selector: &Option<String>, id_in_element: &Option<&String>, is_case_sensitive: bool) -> bool {
     return match id_in_selector {
         Some(id_in_selector) => {
-            return Some(id_in_selector) == *id_in_element
+            return id_in_element.is_some_and(|id_in_element| check_if_names_equal(id_in_selector, id_in_element, is_case_sensitive))
         },
         _ => true
     };
 }
 
-fn check_if_classes_matched(classes_in_selector: &Vec<String>, classes_in_element: HashSet<&str>) -> bool {
+fn check_if_classes_matched(classes_in_selector: &Vec<String>, classes_in_element: &HashSet<String>, is_case_sensitive: bool) -> bool {
     for class in classes_in_selector {
-        if !classes_in_element.contains(class.as_str()) {
+        let is_matched = match is_case_sensitive {
+            true => classes_in_element.contains(class),
+            false => classes_in_element.iter().any(|class_in_element| class_in_element.eq_ignore_ascii_case(class))
+        };
+
+        if !is_matched {
             return false;
         }
     }
@@ -56,58 +104,156 @@ fn check_if_classes_matched(classes_in_selector: &Vec<String>, classes_in_elemen
     return true;
 }
 
-fn check_if_rule_and_element_match<'a>(rule: &'a Rule, element: &ElementData) -> Option<((usize, usize, usize), &'a Rule)> {
-    rule.selectors
+fn check_if_names_equal(a: &str, b: &str, is_case_sensitive: bool) -> bool {
+    return match is_case_sensitive {
+        true => a == b,
+        false => a.eq_ignore_ascii_case(b)
+    };
+}
+
+// A rule that matches through several of its selectors takes the highest specificity among them.
+fn check_if_rule_and_element_match<'a>(rule: &'a Rule, dom: &Dom, node_id: NodeId) -> Option<((usize, usize, usize), &'a Rule)> {
+    return rule.selectors
         .iter()
-        .find(|selector| check_if_selector_and_element_match(selector, element))
-        .map(|selector| (selector.get_specificity(), rule))
+        .filter(|selector| check_if_selector_and_element_match(selector, dom, node_id))
+        .map(|selector| selector.get_specificity())
+        .max()
+        .map(|specificity| (specificity, rule));
 }
 
-fn match_rules_with_element<'a>(rules: &'a Vec<Rule>, element_data: &ElementData) -> Vec<((usize, usize, usize), &'a Rule)> {
+fn match_rules_with_element<'a>(rules: &'a [Rule], dom: &Dom, node_id: NodeId) -> Vec<((usize, usize, usize), &'a Rule)> {
     return rules
         .iter()
-        .filter_map(|rule| check_if_rule_and_element_match(rule, element_data))
+        .filter_map(|rule| check_if_rule_and_element_match(rule, dom, node_id))
         .collect();
 }
 
-fn create_css_properties(stylesheet: &Stylesheet, element_data: &ElementData) -> HashMap<String, DeclarationValue> {
-    let mut css_properties = HashMap::new();
-    let mut rules = match_rules_with_element(&stylesheet.rules, &element_data);
+fn parse_inline_style(element_data: &ElementData) -> Vec<Declaration> {
+    let style = match element_data.get_attribute("style") {
+        Some(style) => style,
+        None => return Vec::new()
+    };
+
+    let mut parser = CSSParser::new(style.clone());
+    let declarations = parser.parse_inline_declarations();
+
+    for warning in parser.get_warnings() {
+        warn!("In style attribute on <{}>: {}", element_data.tag_name, warning);
+    }
+
+    return declarations;
+}
+
+// Properties an element takes from its parent when no rule sets them.
+const INHERITED_PROPERTIES: [&str; 1] = ["white-space"];
+
+// The user agent's defaults for elements that preserve their formatting.
+fn create_default_css_properties(element_data: &ElementData) -> HashMap<String, DeclarationValue> {
+    let white_space = match element_data.tag_name.as_str() {
+        "pre" | "listing" | "plaintext" | "xmp" => "pre",
+        "textarea" => "pre-wrap",
+        "nobr" => "nowrap",
+        _ => return HashMap::new()
+    };
+
+    return HashMap::from([(String::from("white-space"), DeclarationValue::Keyword(String::from(white_space)))]);
+}
+
+// Returns the rules that match the element, from the lowest to the highest precedence.
+fn match_stylesheets_with_element<'a>(stylesheets: &'a [Stylesheet], dom: &Dom, node_id: NodeId) -> Vec<&'a Rule> {
+    let mut rules: Vec<((usize, usize, usize), &Rule)> = stylesheets
+        .iter()
+        .flat_map(|stylesheet| match_rules_with_element(&stylesheet.rules, dom, node_id))
+        .collect();
+    // The sort is stable, so among equally specific rules the one that comes later in document order wins.
     rules.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-    for (_, rule) in rules {
+    return rules.into_iter().map(|(_, rule)| rule).collect();
+}
+
+fn create_css_properties(matched_rules: &Vec<&Rule>, element_data: &ElementData) -> HashMap<String, DeclarationValue> {
+    let mut css_properties = create_default_css_properties(element_data);
+
+    for rule in matched_rules {
         for declaration in &rule.declarations {
             css_properties.insert(declaration.name.clone(), declaration.value.clone());
         }
     }
 
+    // Declarations in the style attribute take precedence over any selector.
+    for declaration in parse_inline_style(element_data) {
+        css_properties.insert(declaration.name, declaration.value);
+    }
+
     return css_properties;
 }
 
-pub fn create_styled_node<'a>(dom_node: &'a Node, stylesheet: &Stylesheet) -> StyledNode<'a> {
-    let css_properties = match &dom_node.node_type {
-        NodeType::Text(_) => HashMap::new(),
-        NodeType::Element(element) => create_css_properties(stylesheet, &element)
+// Comments and doctypes carry no content to style or lay out.
+fn is_rendered_node(dom_node: &Node) -> bool {
+    return match &dom_node.node_type {
+        NodeType::Document | NodeType::Text(_) | NodeType::Element(_) => true,
+        NodeType::Comment(_) | NodeType::Doctype(_) => false
+    };
+}
+
+// Builds the style tree for layout, with the white space of its text already processed.
+pub fn create_styled_node(dom: &Dom, node_id: NodeId, stylesheets: &Vec<Stylesheet>) -> StyledNode {
+    let mut styled_node = create_inheriting_styled_node(dom, node_id, stylesheets, &HashMap::new());
+    process_white_space(&mut styled_node);
+    return styled_node;
+}
+
+fn create_inheriting_styled_node(dom: &Dom, node_id: NodeId, stylesheets: &Vec<Stylesheet>, parent_css_properties: &HashMap<String, DeclarationValue>) -> StyledNode {
+    let dom_node = dom.get_node(node_id);
+    let (matched_rules, mut css_properties) = match &dom_node.node_type {
+        NodeType::Element(element) => {
+            let matched_rules = match_stylesheets_with_element(stylesheets, dom, node_id);
+            let css_properties = create_css_properties(&matched_rules, &element);
+            (matched_rules, css_properties)
+        },
+        NodeType::Document | NodeType::Text(_) | NodeType::Comment(_) | NodeType::Doctype(_) => (Vec::new(), HashMap::new())
+    };
+
+    for name in INHERITED_PROPERTIES {
+        if let (false, Some(value)) = (css_properties.contains_key(name), parent_css_properties.get(name)) {
+            css_properties.insert(String::from(name), value.clone());
+        }
+    }
+
+    let text = match &dom_node.node_type {
+        NodeType::Text(text) => Some(text.clone()),
+        _ => None
     };
 
+    let children = dom.children(node_id)
+        .filter(|child_id| is_rendered_node(dom.get_node(*child_id)))
+        .map(|child_id| create_inheriting_styled_node(dom, child_id, stylesheets, &css_properties))
+        .collect();
+
     return StyledNode {
-        dom_node,
+        node_id,
         css_properties,
-        children: dom_node.children.iter().map(|child_node| create_styled_node(child_node, stylesheet)).collect()
+        matched_rule_spans: matched_rules.iter().filter_map(|rule| rule.source_span).collect(),
+        text,
+        children
     };
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::HashSet;
     use std::ptr;
+    use crate::css_parser::color_value::ColorValue;
     use crate::css_parser::declaration::Declaration;
     use crate::css_parser::declaration_value::DeclarationValue;
+    use crate::css_parser::length_unit::LengthUnit;
     use crate::css_parser::rule::Rule;
     use crate::css_parser::selector::Selector;
     use crate::css_parser::stylesheet::Stylesheet;
-    use crate::html_parser::element_data::ElementData;
-    use crate::style_tree_builder::{check_if_classes_matched, check_if_ids_matched, check_if_rule_and_element_match, check_if_tags_matched, create_css_properties, match_rules_with_element};
+    use crate::css_parser::CSSParser;
+    use crate::html_parser::HTMLParser;
+    use crate::html_parser::dom::Dom;
+    use crate::style_tree_builder::{check_if_classes_matched, check_if_ids_matched, check_if_rule_and_element_match, check_if_selector_and_element_match, check_if_tags_matched, create_css_properties, create_styled_node, match_rules_with_element, match_stylesheets_with_element};
 
     #[test]
     fn test_check_if_tags_matched() {
@@ -116,6 +262,9 @@ mod tests {
 
         assert!(check_if_tags_matched(&tag_in_selector, &tag_in_element));
 
+        let tag_in_selector = Some(String::from("DIV"));
+        assert!(check_if_tags_matched(&tag_in_selector, &tag_in_element));
+
         let tag_in_element = String::from("dive");
         assert!(!check_if_tags_matched(&tag_in_selector, &tag_in_element));
 
@@ -130,34 +279,98 @@ mod tests {
         let id_in_selector = Some(string_abc_1);
         let id_in_element = Some(&string_abc_2);
 
-        assert!(check_if_ids_matched(&id_in_selector, &id_in_element));
+        assert!(check_if_ids_matched(&id_in_selector, &id_in_element, true));
 
         let string_abcd = String::from("abcd");
         let id_in_element = Some(&string_abcd);
-        assert!(!check_if_ids_matched(&id_in_selector, &id_in_element));
+        assert!(!check_if_ids_matched(&id_in_selector, &id_in_element, true));
 
         let id_in_element = None;
-        assert!(!check_if_ids_matched(&id_in_selector, &id_in_element));
+        assert!(!check_if_ids_matched(&id_in_selector, &id_in_element, true));
 
         let id_in_selector = None;
-        assert!(check_if_ids_matched(&id_in_selector, &id_in_element));
+        assert!(check_if_ids_matched(&id_in_selector, &id_in_element, true));
 
         let id_in_element = Some(&string_abc_2);
-        assert!(check_if_ids_matched(&id_in_selector, &id_in_element));
+        assert!(check_if_ids_matched(&id_in_selector, &id_in_element, true));
+
+        let id_in_selector = Some(String::from("ABC"));
+        assert!(!check_if_ids_matched(&id_in_selector, &id_in_element, true));
+        assert!(check_if_ids_matched(&id_in_selector, &id_in_element, false));
     }
 
     #[test]
     fn test_check_if_classes_matched() {
         let classes_in_selector = vec![String::from("class_one"), String::from("class_two"), String::from("class_three")];
-        let classes_in_element = HashSet::from(["class_one", "class_two", "class_three"]);
-        assert!(check_if_classes_matched(&classes_in_selector, classes_in_element));
+        let classes_in_element = HashSet::from([String::from("class_one"), String::from("class_two"), String::from("class_three")]);
+        assert!(check_if_classes_matched(&classes_in_selector, &classes_in_element, true));
 
-        let classes_in_element = HashSet::from(["class_one", "class_three"]);
-        assert!(!check_if_classes_matched(&classes_in_selector, classes_in_element));
+        let classes_in_element = HashSet::from([String::from("class_one"), String::from("class_three")]);
+        assert!(!check_if_classes_matched(&classes_in_selector, &classes_in_element, true));
 
         let classes_in_selector = vec![];
-        let classes_in_element = HashSet::from(["class_one", "class_two", "class_three"]);
-        assert!(check_if_classes_matched(&classes_in_selector, classes_in_element));
+        let classes_in_element = HashSet::from([String::from("class_one"), String::from("class_two"), String::from("class_three")]);
+        assert!(check_if_classes_matched(&classes_in_selector, &classes_in_element, true));
+
+        let classes_in_selector = vec![String::from("Class_One")];
+        assert!(!check_if_classes_matched(&classes_in_selector, &classes_in_element, true));
+        assert!(check_if_classes_matched(&classes_in_selector, &classes_in_element, false));
+    }
+
+    #[test]
+    fn test_check_if_selector_and_element_match_with_combinators() {
+        let dom = HTMLParser::new(String::from("<nav class=menu><ul><li><a id=link>x</a></li></ul></nav><div><a id=other>y</a></div>")).parse();
+        let link = dom.get_element_by_id(dom.get_document(), "link").unwrap();
+        let other = dom.get_element_by_id(dom.get_document(), "other").unwrap();
+
+        let cases = [
+            ("nav a", true, false),
+            ("nav > a", false, false),
+            (".menu li > a", true, false),
+            ("ul > li a", true, false),
+            ("body > * > ul a", true, false),
+            ("body > * > li a", false, false),
+            ("body > nav a", true, false),
+            ("html a#other", false, true),
+            ("li a, div > a", true, true),
+            ("div a", false, true)
+        ];
+
+        for (selectors, matches_link, matches_other) in cases {
+            let selectors = CSSParser::new(String::from(selectors)).parse_selector_list().unwrap();
+            let matches = |node_id| selectors.iter().any(|selector| check_if_selector_and_element_match(selector, &dom, node_id));
+            assert_eq!((matches_link, matches_other), (matches(link), matches(other)), "{}", selectors[0]);
+        }
+    }
+
+    #[test]
+    fn test_check_if_selector_and_element_match_with_sibling_combinators() {
+        let dom = HTMLParser::new(String::from("<div><h2>a</h2><label>b</label> text <!--c--><input id=input><p id=first>d</p><p id=second>e</p></div><p id=outside>f</p>"))
+            .parse();
+        let find = |id| dom.get_element_by_id(dom.get_document(), id).unwrap();
+        let (input, first, second, outside) = (find("input"), find("first"), find("second"), find("outside"));
+
+        let cases = [
+            ("label + input", true, false, false, false),
+            ("h2 + input", false, false, false, false),
+            ("h2 ~ input", true, false, false, false),
+            ("input + p", false, true, false, false),
+            ("h2 ~ p", false, true, true, false),
+            ("p ~ p", false, false, true, false),
+            ("label + input ~ p", false, true, true, false),
+            ("div ~ p", false, false, false, true),
+            ("div p ~ #second", false, false, true, false)
+        ];
+
+        for (selectors, matches_input, matches_first, matches_second, matches_outside) in cases {
+            let selectors = CSSParser::new(String::from(selectors)).parse_selector_list().unwrap();
+            let matches = |node_id| selectors.iter().any(|selector| check_if_selector_and_element_match(selector, &dom, node_id));
+            assert_eq!(
+                (matches_input, matches_first, matches_second, matches_outside),
+                (matches(input), matches(first), matches(second), matches(outside)),
+                "{}", selectors[0]
+            );
+        }
     }
 
     #[test]
@@ -166,17 +379,23 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("div")),
                 id: None,
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: Some(String::from("abc")),
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: None,
-                classes: vec![String::from("class_one"), String::from("class_two")]
+                classes: vec![String::from("class_one"), String::from("class_two")],
+                combinator: None,
+                source_span: None
             },
         ];
 
@@ -184,37 +403,43 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("div")),
                 id: None,
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: Some(String::from("abc")),
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: None,
-                classes: vec![String::from("class_one"), String::from("class_two"), String::from("class_three")]
+                classes: vec![String::from("class_one"), String::from("class_two"), String::from("class_three")],
+                combinator: None,
+                source_span: None
             },
         ];
 
         let rules = vec![
             Rule {
                 selectors: selectors_one,
-                declarations: Vec::new()
+                declarations: Vec::new(),
+                source_span: None
             },
             Rule {
                 selectors: selectors_two,
-                declarations: Vec::new()
+                declarations: Vec::new(),
+                source_span: None
             },
         ];
 
-        let element_matches = ElementData {
-            tag_name: String::from("p"),
-            attributes: HashMap::from([(String::from("class"), String::from("class_one class_two"))])
-        };
+        let mut dom = Dom::new();
+        let element_matches = dom.create_element(String::from("p"), vec![(String::from("class"), String::from("class_one class_two"))]);
 
-        let result = match_rules_with_element(&rules, &element_matches);
+        let result = match_rules_with_element(&rules, &dom, element_matches);
         assert_eq!(1, result.len());
     }
 
@@ -224,39 +449,41 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("div")),
                 id: None,
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: Some(String::from("abc")),
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: None,
-                classes: vec![String::from("class_one"), String::from("class_two")]
+                classes: vec![String::from("class_one"), String::from("class_two")],
+                combinator: None,
+                source_span: None
             },
         ];
 
         let rule = Rule {
             selectors,
-            declarations: Vec::new()
+            declarations: Vec::new(),
+            source_span: None
         };
 
-        let element_matches = ElementData {
-            tag_name: String::from("p"),
-            attributes: HashMap::from([(String::from("class"), String::from("class_one class_two"))])
-        };
+        let mut dom = Dom::new();
+        let element_matches = dom.create_element(String::from("p"), vec![(String::from("class"), String::from("class_one class_two"))]);
 
-        let result = check_if_rule_and_element_match(&rule, &element_matches).unwrap();
+        let result = check_if_rule_and_element_match(&rule, &dom, element_matches).unwrap();
         assert_eq!((0, 2, 0), result.0);
         assert!(ptr::eq(&rule, result.1));
 
-        let element_not_match = ElementData {
-            tag_name: String::from("p"),
-            attributes: HashMap::new()
-        };
-        let result = check_if_rule_and_element_match(&rule, &element_not_match);
+        let element_not_match = dom.create_element(String::from("p"), Vec::new());
+        let result = check_if_rule_and_element_match(&rule, &dom, element_not_match);
         assert!(result.is_none());
     }
 
@@ -266,17 +493,23 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("div")),
                 id: None,
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: Some(String::from("abc")),
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: None,
-                classes: vec![String::from("class_one"), String::from("class_two")]
+                classes: vec![String::from("class_one"), String::from("class_two")],
+                combinator: None,
+                source_span: None
             },
         ];
 
@@ -284,17 +517,23 @@ mod tests {
             Selector {
                 tag_name: Some(String::from("div")),
                 id: None,
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: Some(String::from("abc")),
-                classes: Vec::new()
+                classes: Vec::new(),
+                combinator: None,
+                source_span: None
             },
             Selector {
                 tag_name: None,
                 id: None,
-                classes: vec![String::from("class_one"), String::from("class_two"), String::from("class_three")]
+                classes: vec![String::from("class_one"), String::from("class_two"), String::from("class_three")],
+                combinator: None,
+                source_span: None
             },
         ];
 
@@ -304,31 +543,179 @@ mod tests {
                 declarations: vec![
                     Declaration {
                         name: String::from("display"),
-                        value: DeclarationValue::Keyword(String::from("none"))
+                        value: DeclarationValue::Keyword(String::from("none")),
+                        source_span: None
                     }
-                ]
+                ],
+                source_span: None
             },
             Rule {
                 selectors: selectors_two,
                 declarations: vec![
                     Declaration {
                         name: String::from("appearance"),
-                        value: DeclarationValue::Keyword(String::from("auto"))
+                        value: DeclarationValue::Keyword(String::from("auto")),
+                        source_span: None
                     }
-                ]
+                ],
+                source_span: None
             },
         ];
 
         let stylesheet = Stylesheet {
-            rules
+            rules,
+            at_rules: Vec::new()
         };
 
-        let element_matches = ElementData {
-            tag_name: String::from("p"),
-            attributes: HashMap::from([(String::from("class"), String::from("class_one class_two"))])
-        };
+        let mut dom = Dom::new();
+        let element_matches = dom.create_element(String::from("p"), vec![(String::from("class"), String::from("class_one class_two"))]);
 
-        let result = create_css_properties(&stylesheet, &element_matches);
+        let result = create_css_properties(&match_stylesheets_with_element(&vec![stylesheet], &dom, element_matches), dom.get_element_data(element_matches).unwrap());
         assert_eq!("{\"display\": Keyword(\"none\")}", format!("{:?}", result));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_create_css_properties_from_multiple_stylesheets() {
+        let create_stylesheet = |class: &str, display: &str| Stylesheet {
+            rules: vec![
+                Rule {
+                    selectors: vec![
+                        Selector {
+                            tag_name: None,
+                            id: None,
+                            classes: vec![String::from(class)],
+                            combinator: None,
+                            source_span: None
+                        }
+                    ],
+                    declarations: vec![
+                        Declaration {
+                            name: String::from("display"),
+                            value: DeclarationValue::Keyword(String::from(display)),
+                            source_span: None
+                        }
+                    ],
+                    source_span: None
+                }
+            ],
+            at_rules: Vec::new()
+        };
+
+        let mut dom = Dom::new();
+        let element = dom.create_element(String::from("p"), vec![(String::from("class"), String::from("class_one class_two"))]);
+
+        let stylesheets = vec![create_stylesheet("class_one", "none"), create_stylesheet("class_two", "block")];
+        let result = create_css_properties(&match_stylesheets_with_element(&stylesheets, &dom, element), dom.get_element_data(element).unwrap());
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("block"))), result.get("display"));
+
+        let stylesheets = vec![create_stylesheet("class_two", "block"), create_stylesheet("class_one", "none")];
+        let result = create_css_properties(&match_stylesheets_with_element(&stylesheets, &dom, element), dom.get_element_data(element).unwrap());
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("none"))), result.get("display"));
+    }
+
+    #[test]
+    fn test_create_css_properties_uses_highest_matching_specificity() {
+        let dom = HTMLParser::new(String::from("<div id=x class=link><a id=target>x</a></div>")).parse();
+        let target = dom.get_element_by_id(dom.get_document(), "target").unwrap();
+
+        // "#x a" outweighs ".link a", even though the rule also matches through the less specific "a".
+        let stylesheets = vec![CSSParser::new(String::from("a, #x a { display: none; } .link a { display: block; }")).parse()];
+        let matched_rules = match_stylesheets_with_element(&stylesheets, &dom, target);
+        let result = create_css_properties(&matched_rules, dom.get_element_data(target).unwrap());
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("none"))), result.get("display"));
+
+        let specificities: Vec<(usize, usize, usize)> = match_rules_with_element(&stylesheets[0].rules, &dom, target)
+            .into_iter()
+            .map(|(specificity, _)| specificity)
+            .collect();
+        assert_eq!(vec![(1, 0, 1), (0, 1, 1)], specificities);
+    }
+
+    #[test]
+    fn test_create_styled_node_skips_comments_and_doctypes() {
+        let mut dom = Dom::new();
+        let html = dom.create_element(String::from("html"), Vec::new());
+        let doctype = dom.create_doctype(String::from("html"), None, None);
+        let comment = dom.create_comment(String::from("comment"));
+        let body = dom.create_element(String::from("body"), Vec::new());
+        let text = dom.create_text(String::from("text"));
+        let body_comment = dom.create_comment(String::from("comment"));
+        dom.append_child(html, doctype).unwrap();
+        dom.append_child(html, comment).unwrap();
+        dom.append_child(html, body).unwrap();
+        dom.append_child(body, text).unwrap();
+        dom.append_child(body, body_comment).unwrap();
+
+        let styled_node = create_styled_node(&dom, html, &Vec::new());
+        assert_eq!(1, styled_node.children.len());
+        assert_eq!(1, styled_node.children[0].children.len());
+    }
+
+    #[test]
+    fn test_create_css_properties_with_inline_style() {
+        let stylesheet = Stylesheet {
+            rules: vec![
+                Rule {
+                    selectors: vec![
+                        Selector {
+                            tag_name: None,
+                            id: Some(String::from("main")),
+                            classes: Vec::new(),
+                            combinator: None,
+                            source_span: None
+                        }
+                    ],
+                    declarations: vec![
+                        Declaration {
+                            name: String::from("display"),
+                            value: DeclarationValue::Keyword(String::from("block")),
+                            source_span: None
+                        },
+                        Declaration {
+                            name: String::from("margin-left"),
+                            value: DeclarationValue::Length(8.0, LengthUnit::Px),
+                            source_span: None
+                        }
+                    ],
+                    source_span: None
+                }
+            ],
+            at_rules: Vec::new()
+        };
+
+        let mut dom = Dom::new();
+        let element = dom.create_element(String::from("div"), vec![
+            (String::from("id"), String::from("main")),
+            (String::from("style"), String::from("color: #ff0000; margin-left: 4px"))
+        ]);
+
+        let result = create_css_properties(&match_stylesheets_with_element(&vec![stylesheet], &dom, element), dom.get_element_data(element).unwrap());
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("block"))), result.get("display"));
+        assert_eq!(Some(&DeclarationValue::Length(4.0, LengthUnit::Px)), result.get("margin-left"));
+        assert_eq!(Some(&DeclarationValue::Color(ColorValue { r: 255, g: 0, b: 0, a: 255 })), result.get("color"));
+
+        let element = dom.create_element(String::from("div"), vec![(String::from("style"), String::from("color: red margin-left: 4px"))]);
+
+        assert!(create_css_properties(&match_stylesheets_with_element(&Vec::new(), &dom, element), dom.get_element_data(element).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_create_styled_node_inherits_white_space() {
+        let mut dom = Dom::new();
+        let div = dom.create_element(String::from("div"), vec![(String::from("style"), String::from("white-space: nowrap"))]);
+        let pre = dom.create_element(String::from("pre"), Vec::new());
+        let pre_text = dom.create_text(String::from(" a "));
+        let text = dom.create_text(String::from(" b "));
+        dom.append_child(div, pre).unwrap();
+        dom.append_child(pre, pre_text).unwrap();
+        dom.append_child(div, text).unwrap();
+
+        let styled_node = create_styled_node(&dom, div, &Vec::new());
+        let pre_text = &styled_node.children[0].children[0];
+        assert_eq!(Some(&String::from(" a ")), pre_text.text.as_ref());
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("pre"))), pre_text.get_css_value_by_name(String::from("white-space")));
+
+        let text = &styled_node.children[1];
+        assert_eq!(Some(&DeclarationValue::Keyword(String::from("nowrap"))), text.get_css_value_by_name(String::from("white-space")));
+    }
+}
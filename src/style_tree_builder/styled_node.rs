@@ -1,16 +1,22 @@
 use std::collections::HashMap;
-use crate::html_parser::node::Node as DomNode;
+use crate::html_parser::node_id::NodeId;
 use crate::css_parser::declaration_value::DeclarationValue;
 use crate::css_parser::declaration_value::DeclarationValue::Keyword;
 use crate::layout_tree_builder::display_type::DisplayType;
+use crate::source_span::SourceSpan;
+use crate::white_space_processor::white_space_type::WhiteSpaceType;
 
-pub struct StyledNode<'a> {
-    pub(crate) dom_node: &'a DomNode,
+pub struct StyledNode {
+    pub(crate) node_id: NodeId,
     pub(crate) css_properties: HashMap<String, DeclarationValue>,
-    pub(crate) children: Vec<StyledNode<'a>>
+    // The spans of the rules that matched the node, from the lowest to the highest precedence.
+    pub(crate) matched_rule_spans: Vec<SourceSpan>,
+    // The content of a text node, after white space processing.
+    pub(crate) text: Option<String>,
+    pub(crate) children: Vec<StyledNode>
 }
 
-impl<'a> StyledNode<'a> {
+impl StyledNode {
     pub fn get_css_value_by_name(&self, css_name: String) -> Option<&DeclarationValue> {
         return self.css_properties.get(&css_name)
     }
@@ -24,31 +30,49 @@ impl<'a> StyledNode<'a> {
                     "block" => DisplayType::Block,
                     "inline" => DisplayType::Inline,
                     "none" => DisplayType::None,
-                    _ => panic!("None-supported display type. ")
+                    // Unsupported values such as flex fall back to the initial value.
+                    _ => DisplayType::Inline
                 }
             },
             _ => DisplayType::Inline
         }
     }
+
+    pub fn get_white_space_value(&self) -> WhiteSpaceType {
+        let white_space_value = self.get_css_value_by_name(String::from("white-space"));
+
+        match white_space_value {
+            Some(Keyword(white_space_keyword)) => {
+                match white_space_keyword.as_str() {
+                    "nowrap" => WhiteSpaceType::Nowrap,
+                    "pre" => WhiteSpaceType::Pre,
+                    "pre-wrap" => WhiteSpaceType::PreWrap,
+                    "pre-line" => WhiteSpaceType::PreLine,
+                    "break-spaces" => WhiteSpaceType::BreakSpaces,
+                    _ => WhiteSpaceType::Normal
+                }
+            },
+            _ => WhiteSpaceType::Normal
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
     use crate::css_parser::declaration_value::DeclarationValue;
-    use crate::html_parser::node::Node;
-    use crate::html_parser::node_type::NodeType;
+    use crate::html_parser::dom::Dom;
     use crate::layout_tree_builder::display_type::DisplayType;
     use crate::style_tree_builder::styled_node::StyledNode;
+    use crate::white_space_processor::white_space_type::WhiteSpaceType;
 
     #[test]
     fn test_get_css_value_by_name() {
         let mut styled_node = StyledNode {
-            dom_node: &Node {
-                children: Vec::new(),
-                node_type: NodeType::Text(String::from("text"))
-            },
+            node_id: Dom::new().get_document(),
             css_properties: HashMap::from([(String::from("display"), DeclarationValue::Keyword(String::from("none")))]),
+            matched_rule_spans: Vec::new(),
+            text: None,
             children: Vec::new()
         };
         assert_eq!("none", format!("{}", styled_node.get_css_value_by_name(String::from("display")).unwrap()));
@@ -60,16 +84,36 @@ mod tests {
     #[test]
     fn test_get_display_value() {
         let mut styled_node = StyledNode {
-            dom_node: &Node {
-                children: Vec::new(),
-                node_type: NodeType::Text(String::from("text"))
-            },
+            node_id: Dom::new().get_document(),
             css_properties: HashMap::from([(String::from("display"), DeclarationValue::Keyword(String::from("none")))]),
+            matched_rule_spans: Vec::new(),
+            text: None,
             children: Vec::new()
         };
         assert_eq!(DisplayType::None, styled_node.get_display_value());
 
+        styled_node.css_properties = HashMap::from([(String::from("display"), DeclarationValue::Keyword(String::from("flex")))]);
+        assert_eq!(DisplayType::Inline, styled_node.get_display_value());
+
         styled_node.css_properties = HashMap::new();
         assert_eq!(DisplayType::Inline, styled_node.get_display_value());
     }
+
+    #[test]
+    fn test_get_white_space_value() {
+        let mut styled_node = StyledNode {
+            node_id: Dom::new().get_document(),
+            css_properties: HashMap::from([(String::from("white-space"), DeclarationValue::Keyword(String::from("pre-line")))]),
+            matched_rule_spans: Vec::new(),
+            text: Some(String::from("text")),
+            children: Vec::new()
+        };
+        assert_eq!(WhiteSpaceType::PreLine, styled_node.get_white_space_value());
+
+        styled_node.css_properties = HashMap::from([(String::from("white-space"), DeclarationValue::Keyword(String::from("unknown")))]);
+        assert_eq!(WhiteSpaceType::Normal, styled_node.get_white_space_value());
+
+        styled_node.css_properties = HashMap::new();
+        assert_eq!(WhiteSpaceType::Normal, styled_node.get_white_space_value());
+    }
 }
@@ -0,0 +1,200 @@
+use crate::layout_tree_builder::display_type::DisplayType;
+use crate::style_tree_builder::styled_node::StyledNode;
+use crate::white_space_processor::white_space_type::WhiteSpaceType;
+
+pub mod white_space_type;
+
+// Processes the text of a styled tree according to each text node's white-space value, so that
+// layout sees the text as it is rendered. Collapsible white space is collapsed across inline
+// element boundaries, removed at the start and end of every block, and text nodes left empty,
+// such as the white space between two blocks, are removed.
+//
+// Wrapping is not decided here, so nowrap behaves like normal and pre-wrap and break-spaces like pre.
+pub fn process_white_space(styled_node: &mut StyledNode) {
+    process_block_container(styled_node);
+    remove_empty_text_nodes(styled_node);
+}
+
+fn process_block_container(block_container: &mut StyledNode) {
+    // A block starts on a new line, where collapsible spaces are removed.
+    let mut is_after_collapsible_space = true;
+    process_inline_content(block_container, &mut is_after_collapsible_space);
+    trim_trailing_collapsible_spaces(&mut block_container.children);
+}
+
+fn process_inline_content(parent: &mut StyledNode, is_after_collapsible_space: &mut bool) {
+    for index in 0..parent.children.len() {
+        let white_space = parent.children[index].get_white_space_value();
+
+        if let Some(text) = &parent.children[index].text {
+            let processed_text = collapse_white_space(text.as_str(), white_space, is_after_collapsible_space);
+            parent.children[index].text = Some(processed_text);
+            continue;
+        }
+
+        match parent.children[index].get_display_value() {
+            DisplayType::Block => {
+                trim_trailing_collapsible_spaces(&mut parent.children[..index]);
+                process_block_container(&mut parent.children[index]);
+                *is_after_collapsible_space = true;
+            },
+            DisplayType::Inline => process_inline_content(&mut parent.children[index], is_after_collapsible_space),
+            DisplayType::None => {}
+        }
+    }
+}
+
+fn collapse_white_space(text: &str, white_space: WhiteSpaceType, is_after_collapsible_space: &mut bool) -> String {
+    if !white_space.collapses_spaces() {
+        if let Some(last_char) = text.chars().last() {
+            *is_after_collapsible_space = last_char == '\n';
+        }
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '\n' if white_space.preserves_line_feeds() => {
+                // Spaces around a preserved line feed are removed, so each line starts and ends with content.
+                result.truncate(result.trim_end_matches(' ').len());
+                result.push('\n');
+                *is_after_collapsible_space = true;
+            },
+            ' ' | '\t' | '\n' | '\r' | '\x0C' => {
+                if !*is_after_collapsible_space {
+                    result.push(' ');
+                    *is_after_collapsible_space = true;
+                }
+            },
+            _ => {
+                result.push(char);
+                *is_after_collapsible_space = false;
+            }
+        }
+    }
+
+    return result;
+}
+
+// Removes collapsible spaces at the end of the inline content that ends before a block boundary.
+// Returns true once the last piece of inline content has been found.
+fn trim_trailing_collapsible_spaces(nodes: &mut [StyledNode]) -> bool {
+    for node in nodes.iter_mut().rev() {
+        let white_space = node.get_white_space_value();
+
+        if let Some(text) = &mut node.text {
+            if text.is_empty() {
+                continue;
+            }
+
+            if white_space.collapses_spaces() {
+                text.truncate(text.trim_end_matches(' ').len());
+
+                if text.is_empty() {
+                    continue;
+                }
+            }
+
+            return true;
+        }
+
+        match node.get_display_value() {
+            DisplayType::Block => return true,
+            DisplayType::Inline => {
+                if trim_trailing_collapsible_spaces(&mut node.children) {
+                    return true;
+                }
+            },
+            DisplayType::None => {}
+        }
+    }
+
+    return false;
+}
+
+fn remove_empty_text_nodes(styled_node: &mut StyledNode) {
+    styled_node.children.retain(|child| child.text.as_ref().is_none_or(|text| !text.is_empty()));
+
+    for child in &mut styled_node.children {
+        remove_empty_text_nodes(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css_parser::CSSParser;
+    use crate::html_parser::HTMLParser;
+    use crate::style_tree_builder::create_styled_node;
+    use crate::style_tree_builder::styled_node::StyledNode;
+
+    fn collect_texts(styled_node: &StyledNode, texts: &mut Vec<String>) {
+        if let Some(text) = &styled_node.text {
+            texts.push(text.clone());
+        }
+
+        for child in &styled_node.children {
+            collect_texts(child, texts);
+        }
+    }
+
+    fn process_html(html: &str) -> Vec<String> {
+        let dom = HTMLParser::new(String::from(html)).parse();
+        let stylesheet = CSSParser::new(String::from("html, body, div, p, pre, h1 { display: block; } head { display: none; }")).parse();
+        // The style tree comes with its white space processed.
+        let styled_node = create_styled_node(&dom, dom.get_document(), &vec![stylesheet]);
+
+        let mut texts = Vec::new();
+        collect_texts(&styled_node, &mut texts);
+        return texts;
+    }
+
+    #[test]
+    fn test_collapses_white_space_in_normal_text() {
+        assert_eq!(
+            vec!["Hello world", "a ", "b ", "c", "d"],
+            process_html("<div>\n  <p>Hello   \n\t world</p>\n  <p> a <em> b </em> c </p>\n</div>\n<h1> d </h1>\n")
+        );
+    }
+
+    #[test]
+    fn test_removes_white_space_around_blocks_inside_inline_content() {
+        assert_eq!(
+            vec!["a", "b", "c"],
+            process_html("<div> <span> a </span> <p> b </p> <span> </span> c </div>")
+        );
+    }
+
+    #[test]
+    fn test_preserves_white_space_in_pre() {
+        assert_eq!(
+            vec!["Before", "  line 1\n    line 2  \n", "After"],
+            process_html("<p>Before</p>\n<pre>\n  line 1\n    line 2  \n</pre>\n<p>After </p>")
+        );
+    }
+
+    #[test]
+    fn test_pre_line_keeps_line_feeds() {
+        assert_eq!(
+            vec!["a b\nc\nd"],
+            process_html("<p style='white-space: pre-line'>  a   b  \n   c \n\t d  </p>")
+        );
+    }
+
+    #[test]
+    fn test_pre_wrap_and_break_spaces_preserve_spaces() {
+        assert_eq!(
+            vec!["  a  ", " b "],
+            process_html("<p style='white-space: pre-wrap'>  a  </p><p style='white-space: break-spaces'> b </p>")
+        );
+    }
+
+    #[test]
+    fn test_nowrap_collapses_like_normal() {
+        assert_eq!(
+            vec!["a b"],
+            process_html("<p style='white-space: nowrap'>  a  \n b  </p>")
+        );
+    }
+}
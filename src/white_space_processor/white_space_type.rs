@@ -0,0 +1,27 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WhiteSpaceType {
+    Normal,
+    Nowrap,
+    Pre,
+    PreWrap,
+    PreLine,
+    BreakSpaces
+}
+
+impl WhiteSpaceType {
+    // Whether runs of spaces and tabs are collapsed into a single space.
+    pub fn collapses_spaces(&self) -> bool {
+        return match self {
+            WhiteSpaceType::Normal | WhiteSpaceType::Nowrap | WhiteSpaceType::PreLine => true,
+            WhiteSpaceType::Pre | WhiteSpaceType::PreWrap | WhiteSpaceType::BreakSpaces => false
+        };
+    }
+
+    // Whether line feeds are kept as forced line breaks instead of becoming spaces.
+    pub fn preserves_line_feeds(&self) -> bool {
+        return match self {
+            WhiteSpaceType::Normal | WhiteSpaceType::Nowrap => false,
+            WhiteSpaceType::Pre | WhiteSpaceType::PreWrap | WhiteSpaceType::PreLine | WhiteSpaceType::BreakSpaces => true
+        };
+    }
+}